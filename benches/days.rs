@@ -0,0 +1,37 @@
+//! Times the generator and solver of every registered part on the real inputs.
+//!
+//! `cargo bench -- day5 day12` only runs the given days. Set `AOC_BENCH_RUNS`
+//! to change the number of runs and `AOC_BENCH_FORMAT` to `json` or `csv` for
+//! a summary that can be diffed between commits.
+
+use std::{env, fs, process::ExitCode};
+
+use aoc2023::{bench, format::Format, runner};
+
+fn main() -> ExitCode {
+    let days: Vec<String> = env::args()
+        .skip(1)
+        .filter(|a| !a.starts_with('-'))
+        .collect();
+    let runs = env::var("AOC_BENCH_RUNS").map_or(Ok(20), |runs| runs.parse());
+    let format = env::var("AOC_BENCH_FORMAT").map_or(Ok(Format::Table), |f| f.parse());
+    let (Ok(runs), Ok(format)) = (runs, format) else {
+        eprintln!("AOC_BENCH_RUNS must be a number, AOC_BENCH_FORMAT table, json or csv");
+        return ExitCode::from(2);
+    };
+
+    let (measurements, failures) = bench::run_all(
+        |day| days.is_empty() || days.contains(&format!("day{day}")),
+        runs,
+        |day| fs::read_to_string(runner::input_path(day)),
+    );
+    for failure in &failures {
+        eprintln!("{failure}");
+    }
+    println!("{}", bench::summary(&measurements, format));
+    if failures.iter().any(bench::Failure::is_error) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
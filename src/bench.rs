@@ -0,0 +1,256 @@
+use std::{fmt::Display, io, time::Duration};
+
+use itertools::Itertools;
+
+use crate::{
+    error::ParseError,
+    format::{self, Align, Format},
+    runner::{self, Solution},
+};
+
+/// Generator and solver timings of one part over several runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    pub day: u32,
+    pub part: u32,
+    pub runs: usize,
+    pub parse: Timing,
+    pub solve: Timing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub min: Duration,
+    pub median: Duration,
+}
+
+impl Timing {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort_unstable();
+        Self {
+            min: samples.first().copied().unwrap_or_default(),
+            median: samples.get(samples.len() / 2).copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Runs `solution` on `input` `runs` times.
+pub fn measure(solution: Solution, input: &str, runs: usize) -> Result<Measurement, ParseError> {
+    let (parse, solve) = (0..runs.max(1))
+        .map(|_| {
+            solution
+                .run(input)
+                .map(|run| (run.parse_time, run.solve_time))
+        })
+        .collect::<Result<(Vec<_>, Vec<_>), _>>()?;
+    Ok(Measurement {
+        day: solution.day,
+        part: solution.part,
+        runs: parse.len(),
+        parse: Timing::from_samples(parse),
+        solve: Timing::from_samples(solve),
+    })
+}
+
+/// A day or part [`run_all`] couldn't measure.
+#[derive(Debug)]
+pub enum Failure {
+    /// The input of the day couldn't be read, the day is skipped.
+    NoInput { day: u32, error: io::Error },
+    /// The generator rejected the input.
+    Parse {
+        day: u32,
+        part: u32,
+        error: ParseError,
+    },
+}
+
+impl Failure {
+    /// Whether the failure is worse than a skipped day.
+    pub fn is_error(&self) -> bool {
+        matches!(self, Failure::Parse { .. })
+    }
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::NoInput { day, error } => write!(f, "skipping day {day}, no input: {error}"),
+            Failure::Parse { day, part, error } => write!(f, "Day {day} - Part {part} : {error}"),
+        }
+    }
+}
+
+/// Measures every registered part of the days accepted by `days`, reading
+/// the input of each day once with `input`.
+pub fn run_all(
+    days: impl Fn(u32) -> bool,
+    runs: usize,
+    input: impl Fn(u32) -> io::Result<String>,
+) -> (Vec<Measurement>, Vec<Failure>) {
+    let mut measurements = Vec::new();
+    let mut failures = Vec::new();
+    let solutions = runner::solutions().filter(|s| days(s.day));
+    for (day, solutions) in &solutions.group_by(|s| s.day) {
+        let input = match input(day) {
+            Ok(input) => input,
+            Err(error) => {
+                failures.push(Failure::NoInput { day, error });
+                continue;
+            }
+        };
+        for solution in solutions {
+            match measure(solution, &input, runs) {
+                Ok(measurement) => measurements.push(measurement),
+                Err(error) => failures.push(Failure::Parse {
+                    day,
+                    part: solution.part,
+                    error,
+                }),
+            }
+        }
+    }
+    (measurements, failures)
+}
+
+const FIELDS: [&str; 7] = [
+    "day",
+    "part",
+    "runs",
+    "parse_min_ns",
+    "parse_median_ns",
+    "solve_min_ns",
+    "solve_median_ns",
+];
+
+impl Measurement {
+    fn values(&self) -> [u128; 7] {
+        [
+            self.day.into(),
+            self.part.into(),
+            self.runs as u128,
+            self.parse.min.as_nanos(),
+            self.parse.median.as_nanos(),
+            self.solve.min.as_nanos(),
+            self.solve.median.as_nanos(),
+        ]
+    }
+}
+
+/// Formats `measurements`, JSON and CSV use nanoseconds so they can be diffed
+/// between commits.
+pub fn summary(measurements: &[Measurement], format: Format) -> String {
+    let rows = match format {
+        Format::Table => {
+            let header = ["day", "part", "generator", "runner"].map(str::to_owned);
+            let rows = measurements.iter().map(|m| {
+                [
+                    m.day.to_string(),
+                    m.part.to_string(),
+                    format!("{:?}", m.parse.median),
+                    format!("{:?}", m.solve.median),
+                ]
+            });
+            [header]
+                .into_iter()
+                .chain(rows)
+                .map(Vec::from)
+                .collect_vec()
+        }
+        Format::Json | Format::Csv => {
+            let rows = measurements
+                .iter()
+                .map(|m| m.values().map(|value| value.to_string()));
+            [FIELDS.map(str::to_owned)]
+                .into_iter()
+                .chain(rows)
+                .map(Vec::from)
+                .collect_vec()
+        }
+    };
+    format::render(&rows, format, |_| Align::Right)
+}
+
+impl Display for Measurement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Day {} - Part {} : generator {:?}, runner {:?} (median of {})",
+            self.day, self.part, self.parse.median, self.solve.median, self.runs
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    fn measurement() -> Measurement {
+        Measurement {
+            day: 9,
+            part: 1,
+            runs: 3,
+            parse: Timing {
+                min: Duration::from_nanos(10),
+                median: Duration::from_nanos(12),
+            },
+            solve: Timing {
+                min: Duration::from_micros(1),
+                median: Duration::from_micros(2),
+            },
+        }
+    }
+
+    #[test]
+    fn test_measure() {
+        let solution = runner::find(9, 1).unwrap();
+        let measurement = measure(solution, "0 3 6 9 12 15", 3).unwrap();
+        assert_eq!((measurement.day, measurement.runs), (9, 3));
+        assert!(measurement.parse.min <= measurement.parse.median);
+        assert!(measure(solution, "0 x", 3).is_err());
+    }
+
+    #[test]
+    fn test_run_all() {
+        let reads = Cell::new(0);
+        let input = |day| {
+            reads.set(reads.get() + 1);
+            match day {
+                9 => Ok("0 3 6 9 12 15".to_owned()),
+                6 => Ok("Time: 7\nDistance: x".to_owned()),
+                _ => Err(io::Error::from(io::ErrorKind::NotFound)),
+            }
+        };
+        let (measurements, failures) = run_all(|day| [6, 9, 10].contains(&day), 2, input);
+        assert_eq!(
+            measurements.iter().map(|m| (m.day, m.part)).collect_vec(),
+            vec![(9, 1), (9, 2)]
+        );
+        assert_eq!(reads.get(), 3);
+        assert_eq!(failures.len(), 3);
+        assert_eq!(failures.iter().filter(|f| f.is_error()).count(), 2);
+        assert!(failures[2].to_string().starts_with("skipping day 10"));
+    }
+
+    #[test]
+    fn test_summary() {
+        let measurements = [measurement()];
+        assert_eq!(
+            summary(&measurements, Format::Csv),
+            "day,part,runs,parse_min_ns,parse_median_ns,solve_min_ns,solve_median_ns\n\
+             9,1,3,10,12,1000,2000"
+        );
+        assert_eq!(
+            summary(&measurements, Format::Json),
+            "[\n  {\"day\": 9, \"part\": 1, \"runs\": 3, \"parse_min_ns\": 10, \
+             \"parse_median_ns\": 12, \"solve_min_ns\": 1000, \"solve_median_ns\": 2000}\n]"
+        );
+        assert_eq!(summary(&[], Format::Json), "[]");
+        assert_eq!(
+            summary(&measurements, Format::Table),
+            "day  part  generator  runner\n  9     1       12ns     2µs"
+        );
+    }
+}
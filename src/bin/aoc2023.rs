@@ -0,0 +1,361 @@
+use std::{
+    env, fs,
+    io::{self, Read},
+    process::ExitCode,
+};
+
+use aoc2023::{
+    bench, day1,
+    day2::{self, Bag, View as BagsView},
+    day7,
+    error::ParseError,
+    format::Format,
+    runner::{self, Run, Solution},
+    verify::{self, Answers},
+};
+
+const USAGE: &str = "\
+usage: aoc2023 run --day <N> [--part <P>] [--input <PATH>]
+       aoc2023 run --all
+       aoc2023 verify [--answers <PATH>]
+       aoc2023 explain --day <N> [--part <P>] [--input <PATH>]
+       aoc2023 bags [--bags <PATH>] [--input <PATH>] [--view violations|matrix|stats]
+                    [--format table|json|csv]
+       aoc2023 ranking [--part <P>] [--input <PATH>] [--format table|json|csv]
+       aoc2023 bench [--day <N>] [--runs <N>] [--format table|json|csv]
+
+The input defaults to input/2023/day<N>.txt, `--input -` reads stdin.
+The answers default to answers.toml, the day 2 bags, one per line, to
+12 red, 13 green, 14 blue.";
+
+enum Command {
+    Run {
+        day: u32,
+        part: Option<u32>,
+        input: Option<String>,
+    },
+    All,
+    Explain {
+        day: u32,
+        part: u32,
+        input: Option<String>,
+    },
+    Verify {
+        answers: String,
+    },
+    Bags {
+        bags: Option<String>,
+        input: Option<String>,
+        view: BagsView,
+        format: Format,
+    },
+    Ranking {
+        part: u32,
+        input: Option<String>,
+        format: Format,
+    },
+    Bench {
+        day: Option<u32>,
+        runs: usize,
+        format: Format,
+    },
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let explain = match args.next().as_deref() {
+        Some("run") => false,
+        Some("explain") => true,
+        Some("verify") => {
+            return match (args.next().as_deref(), args.next()) {
+                (None, _) => Ok(Command::Verify {
+                    answers: "answers.toml".into(),
+                }),
+                (Some("--answers" | "-a"), Some(answers)) => Ok(Command::Verify { answers }),
+                _ => Err("verify only takes --answers <PATH>".into()),
+            };
+        }
+        Some("bags") => return parse_bags_args(args),
+        Some("ranking") => return parse_ranking_args(args),
+        Some("bench") => return parse_bench_args(args),
+        _ => {
+            return Err(
+                "expected the `run`, `explain`, `verify`, `bags`, `ranking` or `bench` command"
+                    .into(),
+            )
+        }
+    };
+    let (mut day, mut part, mut input, mut all) = (None, None, None, false);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+        match arg.as_str() {
+            "--all" => all = true,
+            "--day" | "-d" => day = Some(value()?.parse().map_err(|e| format!("--day: {e}"))?),
+            "--part" | "-p" => part = Some(value()?.parse().map_err(|e| format!("--part: {e}"))?),
+            "--input" | "-i" => input = Some(value()?),
+            _ => return Err(format!("unknown argument {arg}")),
+        }
+    }
+    if explain {
+        return match (all, day) {
+            (false, Some(day)) => Ok(Command::Explain {
+                day,
+                part: part.unwrap_or(2),
+                input,
+            }),
+            _ => Err("explain needs a --day and no --all".into()),
+        };
+    }
+    match (all, day) {
+        (true, None) if part.is_none() && input.is_none() => Ok(Command::All),
+        (true, _) => Err("--all can't be combined with other arguments".into()),
+        (false, Some(day)) => Ok(Command::Run { day, part, input }),
+        (false, None) => Err("missing --day or --all".into()),
+    }
+}
+
+fn parse_bags_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let (mut bags, mut input, mut view, mut format) =
+        (None, None, BagsView::Violations, Format::Table);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {arg}"))?;
+        match arg.as_str() {
+            "--bags" | "-b" => bags = Some(value),
+            "--input" | "-i" => input = Some(value),
+            "--view" | "-v" => view = value.parse()?,
+            "--format" | "-f" => format = value.parse()?,
+            _ => return Err(format!("unknown argument {arg}")),
+        }
+    }
+    Ok(Command::Bags {
+        bags,
+        input,
+        view,
+        format,
+    })
+}
+
+fn parse_ranking_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let (mut part, mut input, mut format) = (2, None, Format::Table);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {arg}"))?;
+        match arg.as_str() {
+            "--part" | "-p" => part = value.parse().map_err(|e| format!("--part: {e}"))?,
+            "--input" | "-i" => input = Some(value),
+            "--format" | "-f" => format = value.parse()?,
+            _ => return Err(format!("unknown argument {arg}")),
+        }
+    }
+    Ok(Command::Ranking {
+        part,
+        input,
+        format,
+    })
+}
+
+fn parse_bench_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let (mut day, mut runs, mut format) = (None, 20, Format::Table);
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("missing value for {arg}"))?;
+        match arg.as_str() {
+            "--day" | "-d" => day = Some(value.parse().map_err(|e| format!("--day: {e}"))?),
+            "--runs" | "-r" => runs = value.parse().map_err(|e| format!("--runs: {e}"))?,
+            "--format" | "-f" => format = value.parse()?,
+            _ => return Err(format!("unknown argument {arg}")),
+        }
+    }
+    Ok(Command::Bench { day, runs, format })
+}
+
+fn read_input(path: &str) -> io::Result<String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path)
+    }
+}
+
+/// Runs `solution` and prints its answer like `cargo aoc` does.
+fn run(solution: Solution, input: &str) -> bool {
+    match solution.run(input) {
+        Ok(Run {
+            answer,
+            parse_time,
+            solve_time,
+        }) => {
+            println!("Day {} - Part {} : {answer}", solution.day, solution.part);
+            println!("\tgenerator: {parse_time:?},");
+            println!("\trunner: {solve_time:?}\n");
+            true
+        }
+        Err(e) => {
+            eprintln!("Day {} - Part {} : {e}", solution.day, solution.part);
+            false
+        }
+    }
+}
+
+/// Annotated report of how `part` of `day` gets its answer, for the days
+/// that support it.
+fn explain(day: u32, part: u32, input: &str) -> Option<Result<String, ParseError>> {
+    match day {
+        1 => Some(Ok(day1::report(input, part))),
+        7 => Some(day7::report(input, part, Format::Table)),
+        _ => None,
+    }
+}
+
+fn main() -> ExitCode {
+    let command = match parse_args(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{e}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut ok = true;
+    match command {
+        Command::Run { day, part, input } => {
+            let solutions: Vec<_> = runner::solutions()
+                .filter(|s| s.day == day && part.is_none_or(|p| s.part == p))
+                .collect();
+            if solutions.is_empty() {
+                eprintln!("no solution registered for day {day}");
+                return ExitCode::FAILURE;
+            }
+            let path = input.unwrap_or_else(|| runner::input_path(day).display().to_string());
+            let input = match read_input(&path) {
+                Ok(input) => input,
+                Err(e) => {
+                    eprintln!("can't read {path}: {e}");
+                    return ExitCode::FAILURE;
+                }
+            };
+            for solution in solutions {
+                ok &= run(solution, &input);
+            }
+        }
+        Command::Explain { day, part, input } => {
+            let path = input.unwrap_or_else(|| runner::input_path(day).display().to_string());
+            match read_input(&path).map(|input| explain(day, part, &input)) {
+                Ok(Some(Ok(report))) => println!("{report}"),
+                Ok(Some(Err(e))) => {
+                    eprintln!("{e}");
+                    ok = false;
+                }
+                Ok(None) => {
+                    eprintln!("day {day} has no explain mode");
+                    ok = false;
+                }
+                Err(e) => {
+                    eprintln!("can't read {path}: {e}");
+                    ok = false;
+                }
+            }
+        }
+        Command::Bags {
+            bags,
+            input,
+            view,
+            format,
+        } => {
+            let path = input.unwrap_or_else(|| runner::input_path(2).display().to_string());
+            let bags = match bags.as_deref().map(fs::read_to_string).transpose() {
+                Ok(bags) => bags,
+                Err(e) => {
+                    eprintln!("can't read the bags: {e}");
+                    return ExitCode::FAILURE;
+                }
+            };
+            let bags = match bags.as_deref() {
+                Some(bags) => Bag::parse_list(bags),
+                None => Ok(vec![Bag::default()]),
+            };
+            let bags = match bags {
+                Ok(bags) => bags,
+                Err(e) => {
+                    eprintln!("invalid bags: {e}");
+                    return ExitCode::FAILURE;
+                }
+            };
+            match read_input(&path).map(|input| day2::report(&input, &bags, view, format)) {
+                Ok(Ok(report)) => println!("{report}"),
+                Ok(Err(e)) => {
+                    eprintln!("{e}");
+                    ok = false;
+                }
+                Err(e) => {
+                    eprintln!("can't read {path}: {e}");
+                    ok = false;
+                }
+            }
+        }
+        Command::All => {
+            for solution in runner::solutions() {
+                let path = runner::input_path(solution.day);
+                match fs::read_to_string(&path) {
+                    Ok(input) => ok &= run(solution, &input),
+                    Err(e) => {
+                        let (day, part) = (solution.day, solution.part);
+                        eprintln!("Day {day} - Part {part} : skipped, can't read {path:?}: {e}\n");
+                    }
+                }
+            }
+        }
+        Command::Verify { answers: path } => {
+            let answers: Answers = match fs::read_to_string(&path) {
+                Ok(content) => match content.parse() {
+                    Ok(answers) => answers,
+                    Err(e) => {
+                        eprintln!("invalid {path}: {e}");
+                        return ExitCode::FAILURE;
+                    }
+                },
+                Err(e) => {
+                    eprintln!("can't read {path}: {e}");
+                    return ExitCode::FAILURE;
+                }
+            };
+            let checks =
+                verify::verify(&answers, |day| fs::read_to_string(runner::input_path(day)));
+            println!("{}", verify::report(&checks));
+            ok = !checks.iter().any(|c| c.status.is_failure());
+        }
+        Command::Ranking {
+            part,
+            input,
+            format,
+        } => {
+            let path = input.unwrap_or_else(|| runner::input_path(7).display().to_string());
+            match read_input(&path).map(|input| day7::report(&input, part, format)) {
+                Ok(Ok(report)) => println!("{report}"),
+                Ok(Err(e)) => {
+                    eprintln!("{e}");
+                    ok = false;
+                }
+                Err(e) => {
+                    eprintln!("can't read {path}: {e}");
+                    ok = false;
+                }
+            }
+        }
+        Command::Bench { day, runs, format } => {
+            let (measurements, failures) = bench::run_all(
+                |d| day.is_none_or(|day| d == day),
+                runs,
+                |day| fs::read_to_string(runner::input_path(day)),
+            );
+            for failure in &failures {
+                eprintln!("{failure}");
+            }
+            ok = !failures.iter().any(bench::Failure::is_error);
+            println!("{}", bench::summary(&measurements, format));
+        }
+    }
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
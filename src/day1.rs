@@ -1,4 +1,30 @@
-use aoc_runner_derive::{aoc, aoc_generator};
+use std::{
+    cmp::{max_by_key, min_by_key, Reverse},
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    ops::Range,
+    sync::LazyLock,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    error::{parse_lines, ParseError},
+    runner::{timed, Solution},
+};
+
+const DIGITS: [(&str, usize); 10] = [
+    ("0", 0),
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
 
 const NUMBERS: [(&str, usize); 9] = [
     ("one", 1),
@@ -12,69 +38,217 @@ const NUMBERS: [(&str, usize); 9] = [
     ("nine", 9),
 ];
 
-#[aoc_generator(day1)]
-pub fn input_generator(input: &str) -> Vec<String> {
-    input.lines().map(str::to_string).collect()
+static DIGITS_ONLY: LazyLock<Vocabulary> = LazyLock::new(|| Vocabulary::new(DIGITS));
+
+static ENGLISH: LazyLock<Vocabulary> =
+    LazyLock::new(|| Vocabulary::new(DIGITS.into_iter().chain(NUMBERS)));
+
+/// A numeral found in a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub value: usize,
+    /// Byte span in the line.
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    next: HashMap<u8, usize>,
+    fail: usize,
+    /// `(word length, value)` of every word ending here, including those
+    /// reached through `fail`.
+    output: Vec<(usize, usize)>,
 }
 
-fn first_number<T: Iterator<Item = char>>(mut input: T) -> usize {
-    input
-        .find(char::is_ascii_digit)
-        .unwrap()
-        .to_digit(10)
-        .unwrap() as usize
+/// Aho-Corasick automaton recognising the words of a word to value
+/// dictionary, so every line is scanned once however many words there are.
+#[derive(Debug)]
+pub struct Vocabulary {
+    nodes: Vec<Node>,
 }
 
-fn find_number(input: &str) -> usize {
-    let mut first = Vec::new();
-    let mut last = Vec::new();
-    for n in NUMBERS {
-        if let Some(f) = input.find(n.0) {
-            first.push((f, n.1));
+impl Vocabulary {
+    /// Builds the automaton, a word given twice keeps its last value.
+    pub fn new<'a>(words: impl IntoIterator<Item = (&'a str, usize)>) -> Self {
+        let mut nodes = vec![Node::default()];
+        for (word, value) in words {
+            assert!(!word.is_empty(), "numerals can't be empty");
+            let mut node = 0;
+            for b in word.bytes() {
+                node = match nodes[node].next.get(&b) {
+                    Some(&next) => next,
+                    None => {
+                        nodes.push(Node::default());
+                        let next = nodes.len() - 1;
+                        nodes[node].next.insert(b, next);
+                        next
+                    }
+                };
+            }
+            nodes[node].output = vec![(word.len(), value)];
         }
-        if let Some(l) = input.rfind(n.0) {
-            last.push((l, n.1));
+
+        // breadth first, so the fail node of a node is always done before it
+        let mut queue: VecDeque<usize> = nodes[0].next.values().copied().collect();
+        while let Some(node) = queue.pop_front() {
+            for (b, next) in nodes[node].next.clone() {
+                let mut fail = nodes[node].fail;
+                while fail != 0 && !nodes[fail].next.contains_key(&b) {
+                    fail = nodes[fail].fail;
+                }
+                let fail = nodes[fail].next.get(&b).copied().unwrap_or(0);
+                let inherited = nodes[fail].output.clone();
+                nodes[next].fail = fail;
+                nodes[next].output.extend(inherited);
+                queue.push_back(next);
+            }
+        }
+        Self { nodes }
+    }
+
+    /// Every numeral in `line` ordered by where it ends, overlapping ones
+    /// like the `eight` and `two` of `eightwo` included.
+    pub fn matches<'a>(&'a self, line: &'a str) -> impl Iterator<Item = Match> + 'a {
+        line.bytes()
+            .enumerate()
+            .scan(0, move |node, (i, b)| {
+                while *node != 0 && !self.nodes[*node].next.contains_key(&b) {
+                    *node = self.nodes[*node].fail;
+                }
+                *node = self.nodes[*node].next.get(&b).copied().unwrap_or(0);
+                Some(
+                    self.nodes[*node]
+                        .output
+                        .iter()
+                        .map(move |&(len, value)| Match {
+                            value,
+                            span: i + 1 - len..i + 1,
+                        }),
+                )
+            })
+            .flatten()
+    }
+
+    /// The first and last numeral of `line` by where they start. Of the
+    /// numerals starting at the same byte the longest wins, so `seventeen`
+    /// beats `seven`.
+    pub fn first_and_last(&self, line: &str) -> Option<(Match, Match)> {
+        self.matches(line).fold(None, |found, m| {
+            let (first, last) = found.unwrap_or_else(|| (m.clone(), m.clone()));
+            Some((
+                min_by_key(first, m.clone(), |m| (m.span.start, Reverse(m.span.len()))),
+                max_by_key(last, m, |m| (m.span.start, m.span.len())),
+            ))
+        })
+    }
+
+    /// The calibration value of `line`, or `None` if it has no numerals.
+    pub fn calibration(&self, line: &str) -> Option<usize> {
+        self.explain(line).value()
+    }
+
+    pub fn explain<'a>(&self, line: &'a str) -> Explanation<'a> {
+        Explanation {
+            line,
+            tokens: self.first_and_last(line),
         }
     }
-    if let Some(first_digit) = input
-        .chars()
-        .enumerate()
-        .find(|n| n.1.is_ascii_digit())
-        .map(|(index, value)| (index, value.to_digit(10).unwrap() as usize))
-    {
-        first.push(first_digit);
-    }
-    if let Some(last_digit) = input
-        .chars()
-        .rev()
-        .enumerate()
-        .find(|n| n.1.is_ascii_digit())
-        .map(|(index, value)| {
-            (
-                input.len() - 1 - index,
-                value.to_digit(10).unwrap() as usize,
-            )
+}
+
+/// The tokens a line's calibration value comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation<'a> {
+    pub line: &'a str,
+    /// First and last token, `None` for lines without any digits.
+    pub tokens: Option<(Match, Match)>,
+}
+
+impl Explanation<'_> {
+    /// The values of the first and last token written one after the other,
+    /// so `ten` and `3` make 103.
+    pub fn value(&self) -> Option<usize> {
+        self.tokens.as_ref().map(|(first, last)| {
+            let digits = last.value.checked_ilog10().unwrap_or(0) + 1;
+            first.value * 10_usize.pow(digits) + last.value
         })
-    {
-        last.push(last_digit);
     }
-    first.into_iter().min_by_key(|(index, _)| *index).unwrap().1 * 10
-        + last.into_iter().max_by_key(|(index, _)| *index).unwrap().1
 }
 
-#[aoc(day1, part1)]
-fn part1(input: &[String]) -> usize {
-    input
-        .iter()
-        .map(|n| first_number(n.chars()) * 10 + first_number(n.chars().rev()))
-        .sum()
+/// The line, followed by `^` under the first and last token and their
+/// values.
+impl Display for Explanation<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (Some((first, last)), Some(value)) = (&self.tokens, self.value()) else {
+            return write!(f, "{}\n  no digits", self.line);
+        };
+        let column = |byte| self.line[..byte].chars().count();
+        let mut markers = vec![' '; self.line.chars().count()];
+        for token in [first, last] {
+            markers[column(token.span.start)..column(token.span.end)].fill('^');
+        }
+        let markers = String::from_iter(markers);
+        write!(
+            f,
+            "{}\n{}\n  {:?} is {} and {:?} is {}, calibration value {}",
+            self.line,
+            markers.trim_end(),
+            &self.line[first.span.clone()],
+            first.value,
+            &self.line[last.span.clone()],
+            last.value,
+            value
+        )
+    }
+}
+
+/// Annotated explanation of every line of `input` for `part`, ending with
+/// the sum of the calibration values. Lines without digits are flagged and
+/// left out of the sum.
+pub fn report(input: &str, part: u32) -> String {
+    let vocabulary: &Vocabulary = if part == 1 { &DIGITS_ONLY } else { &ENGLISH };
+    let explanations = input
+        .lines()
+        .map(|line| vocabulary.explain(line))
+        .collect_vec();
+    let missing = explanations.iter().filter(|e| e.tokens.is_none()).count();
+    let total: usize = explanations.iter().filter_map(Explanation::value).sum();
+    format!(
+        "{}\n\ntotal {total}, {missing} lines without digits",
+        explanations.iter().join("\n")
+    )
+}
+
+/// The calibration value of every line of `input`. Unlike [`report`] a line
+/// without numerals is an error, it would make the sum wrong.
+fn calibrations(input: &str, vocabulary: &Vocabulary) -> Result<Vec<usize>, ParseError> {
+    parse_lines(1, input, |line| {
+        vocabulary
+            .calibration(line)
+            .ok_or_else(|| ParseError::at(line, line, "no digits in the line"))
+    })
+}
+
+fn parse_p1(input: &str) -> Result<Vec<usize>, ParseError> {
+    calibrations(input, &DIGITS_ONLY)
+}
+
+fn parse_p2(input: &str) -> Result<Vec<usize>, ParseError> {
+    calibrations(input, &ENGLISH)
+}
+
+fn part1(calibrations: &[usize]) -> usize {
+    calibrations.iter().sum()
 }
 
-#[aoc(day1, part2)]
-fn part2(input: &[String]) -> usize {
-    input.iter().map(String::as_str).map(find_number).sum()
+fn part2(calibrations: &[usize]) -> usize {
+    calibrations.iter().sum()
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(1, 1, |input| timed(input, parse_p1, part1)),
+    Solution::new(1, 2, |input| timed(input, parse_p2, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -92,7 +266,7 @@ mod tests {
             zoneight234
             7pqrstsixteen
         "};
-        assert_eq!(part2(&input_generator(lines)), 281)
+        assert_eq!(part2(&parse_p2(lines).unwrap()), 281)
     }
 
     #[test]
@@ -108,11 +282,120 @@ mod tests {
         ];
         for (word, value) in parts {
             assert_eq!(
-                find_number(word),
-                value,
+                ENGLISH.calibration(word),
+                Some(value),
                 "{} was not translated correctly",
                 word
             );
         }
     }
+
+    #[test]
+    fn test_overlapping_matches() {
+        let matches = ENGLISH.matches("eightwo3").collect::<Vec<_>>();
+        assert_eq!(
+            matches,
+            vec![
+                Match {
+                    value: 8,
+                    span: 0..5
+                },
+                Match {
+                    value: 2,
+                    span: 4..7
+                },
+                Match {
+                    value: 3,
+                    span: 7..8
+                },
+            ]
+        );
+        assert_eq!(ENGLISH.matches("oneightwone").count(), 4);
+        assert_eq!(ENGLISH.matches("abc").count(), 0);
+    }
+
+    #[test]
+    fn test_vocabulary() {
+        let german = Vocabulary::new([
+            ("eins", 1),
+            ("zwei", 2),
+            ("drei", 3),
+            ("fünf", 5),
+            ("null", 0),
+        ]);
+        assert_eq!(german.calibration("xfünfzweix"), Some(52));
+        assert_eq!(
+            german
+                .matches("zweinsfünf")
+                .map(|m| m.span)
+                .collect::<Vec<_>>(),
+            vec![0..4, 2..6, 6..11]
+        );
+        assert_eq!(german.calibration("one2three"), None);
+
+        let nested = Vocabulary::new([("ten", 10), ("seventeen", 17), ("seven", 7), ("zero", 0)]);
+        let values = nested
+            .matches("seventeen")
+            .map(|m| m.value)
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![7, 17]);
+        assert_eq!(nested.calibration("zeroten"), Some(10));
+    }
+
+    #[test]
+    fn test_multi_digit_values() {
+        let vocabulary = Vocabulary::new(DIGITS.into_iter().chain([("ten", 10), ("twelve", 12)]));
+        assert_eq!(vocabulary.calibration("ten3"), Some(103));
+        assert_eq!(vocabulary.calibration("4xtwelve"), Some(412));
+        assert_eq!(vocabulary.calibration("ten"), Some(1010));
+        assert_eq!(vocabulary.calibration("0ten"), Some(10));
+    }
+
+    #[test]
+    fn test_longest_match_at_start() {
+        for words in [
+            [("seven", 7), ("seventeen", 17)],
+            [("seventeen", 17), ("seven", 7)],
+        ] {
+            let vocabulary = Vocabulary::new(words);
+            let (first, last) = vocabulary.first_and_last("xseventeenx").unwrap();
+            assert_eq!((first.value, last.value), (17, 17));
+            assert_eq!(vocabulary.calibration("seven-seventeen"), Some(717));
+            assert_eq!(vocabulary.calibration("seventeen-seven"), Some(177));
+        }
+    }
+
+    #[test]
+    fn test_explain() {
+        let explanation = ENGLISH.explain("xtwone3four");
+        assert_eq!(explanation.value(), Some(24));
+        assert_eq!(
+            explanation.to_string(),
+            "xtwone3four\n ^^^   ^^^^\n  \"two\" is 2 and \"four\" is 4, calibration value 24"
+        );
+        let explanation = DIGITS_ONLY.explain("treb7uchet");
+        assert_eq!(explanation.to_string().lines().nth(1), Some("    ^"));
+        assert_eq!(explanation.value(), Some(77));
+        assert_eq!(DIGITS_ONLY.explain("abc").value(), None);
+    }
+
+    #[test]
+    fn lines_without_digits() {
+        let error = parse_p1("1abc2\nabc\nfünf7").unwrap_err();
+        assert_eq!(
+            (error.day, error.line, error.text.as_str()),
+            (Some(1), Some(2), "abc")
+        );
+        let error = parse_p1("1abc2\ntwo").unwrap_err();
+        assert_eq!(error.line, Some(2));
+        assert_eq!(parse_p2("1abc2\ntwo").unwrap(), vec![12, 22]);
+    }
+
+    #[test]
+    fn test_report() {
+        let report = report("1abc2\nabc\nfünf7", 1);
+        assert!(report.contains("abc\n  no digits"));
+        assert!(report.contains("fünf7\n    ^\n"));
+        assert!(report.ends_with("total 89, 1 lines without digits"));
+    }
 }
@@ -1,31 +1,40 @@
-use std::{
-    collections::HashSet,
-    ops::{Index, Not},
-};
+use std::{collections::HashSet, ops::Index};
 
-use aoc_runner_derive::{aoc, aoc_generator};
+use crate::{
+    direction::Direction,
+    error::ParseError,
+    grid::Grid,
+    runner::{timed, Solution},
+};
 
-struct Grid<const N: usize, const M: usize> {
+struct Sketch {
     start: (usize, usize),
-    grid: [[Pipe; N]; M],
+    grid: Grid<Pipe>,
 }
 
-impl<const N: usize, const M: usize> Index<(usize, usize)> for Grid<N, M> {
+impl Index<(usize, usize)> for Sketch {
     type Output = Pipe;
 
     fn index(&self, index: (usize, usize)) -> &Self::Output {
-        self.grid.index(index.1).index(index.0)
+        &self.grid[index]
     }
 }
 
-impl<const N: usize, const M: usize> Grid<N, M> {
+impl Sketch {
+    fn width(&self) -> usize {
+        self.grid.width()
+    }
+
+    fn height(&self) -> usize {
+        self.grid.height()
+    }
+
     fn step(
         &self,
         from_possition: (usize, usize),
         direction: Direction,
-        limit: usize,
     ) -> Option<((usize, usize), Direction)> {
-        let new_pos: (usize, usize) = direction.step(from_possition, limit)?;
+        let new_pos: (usize, usize) = direction.step(from_possition, self.grid.size())?;
         if self[new_pos] == Start {
             return Some((new_pos, North));
         }
@@ -34,10 +43,9 @@ impl<const N: usize, const M: usize> Grid<N, M> {
     }
 
     fn find_pipe_start(&self) -> Pipe {
-        let mut parts = [(North, M), (South, M), (East, N), (West, N)]
+        let mut parts = Direction::ALL
             .into_iter()
-            .filter(|(dir, limit)| self.step(self.start, *dir, *limit).is_some())
-            .map(|n| n.0);
+            .filter(|dir| self.step(self.start, *dir).is_some());
         let pipe = match parts.next().unwrap() {
             North => match parts.next().unwrap() {
                 North => unreachable!("north north no direction"),
@@ -61,13 +69,13 @@ impl<const N: usize, const M: usize> Grid<N, M> {
     }
 
     fn find_steps(&self) -> usize {
-        let loop_len = [(North, M), (South, M), (East, N), (West, N)]
+        let loop_len = Direction::ALL
             .into_iter()
-            .find_map(|(mut dir, limit)| -> Option<usize> {
+            .find_map(|mut dir| -> Option<usize> {
                 let mut pos = self.start;
                 let mut n = 0;
                 while self[pos] != Start || n == 0 {
-                    let next = self.step(pos, dir, limit)?;
+                    let next = self.step(pos, dir)?;
                     n += 1;
                     pos = next.0;
                     dir = next.1;
@@ -79,14 +87,14 @@ impl<const N: usize, const M: usize> Grid<N, M> {
     }
 
     fn find_loop_coords(&self) -> HashSet<(usize, usize)> {
-        [(North, M), (South, M), (East, N), (West, N)]
+        Direction::ALL
             .into_iter()
-            .find_map(|(mut dir, limit)| -> Option<HashSet<(usize, usize)>> {
+            .find_map(|mut dir| -> Option<HashSet<(usize, usize)>> {
                 let mut pipe_loop = HashSet::new();
                 let mut pos = self.start;
                 let mut n = 0;
                 while self[pos] != Start || n == 0 {
-                    let next = self.step(pos, dir, limit)?;
+                    let next = self.step(pos, dir)?;
                     n += 1;
                     pos = next.0;
                     dir = next.1;
@@ -101,11 +109,11 @@ impl<const N: usize, const M: usize> Grid<N, M> {
         let loop_coords = self.find_loop_coords();
         let mut found = 0;
         {
-            for x in 0..N {
+            for x in 0..self.width() {
                 let mut inside = false;
                 let mut cross_direction = North;
                 let mut on_pipe = false;
-                for y in 0..M {
+                for y in 0..self.height() {
                     let pos = (x, y);
                     if loop_coords.contains(&pos) {
                         let mut pos = self[pos];
@@ -160,27 +168,6 @@ enum Pipe {
     Start,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
-}
-
-impl Not for Direction {
-    type Output = Direction;
-
-    fn not(self) -> Self::Output {
-        match self {
-            North => South,
-            South => North,
-            East => West,
-            West => East,
-        }
-    }
-}
-
 use Direction::*;
 use Pipe::*;
 
@@ -233,62 +220,38 @@ impl Pipe {
     }
 }
 
-fn checked_add_max(a: usize, b: usize, limit: usize) -> Option<usize> {
-    a.checked_add(b).filter(|n| *n < limit)
-}
-
-impl Direction {
-    fn step(&self, pos: (usize, usize), n: usize) -> Option<(usize, usize)> {
-        let x = pos.0;
-        let y = pos.1;
-        match self {
-            North => Some((x, y.checked_sub(1)?)),
-            South => Some((x, checked_add_max(y, 1, n)?)),
-            East => Some((checked_add_max(x, 1, n)?, y)),
-            West => Some((x.checked_sub(1)?, y)),
-        }
-    }
-}
-
-fn parse_grid<const N: usize, const M: usize>(input: &str) -> Grid<N, M> {
-    let mut grid = [[Ground; N]; M];
-    let mut start = None;
-    for (y, line) in input.lines().enumerate() {
-        assert_eq!(line.len(), N, "input: {line}");
-        for (x, c) in line.char_indices() {
-            let point = c.try_into().unwrap();
-            grid[y][x] = point;
-            if point == Start {
-                start = Some((x, y));
-            }
-        }
-    }
-    Grid {
-        start: start.unwrap(),
-        grid,
-    }
+fn parse(input: &str) -> Result<Sketch, ParseError> {
+    let grid = Grid::parse_with(input, Pipe::try_from).map_err(|e| e.in_day(10))?;
+    let start = grid.position(|pipe| *pipe == Start).ok_or_else(|| {
+        let first = input.lines().next().unwrap_or(input);
+        ParseError::in_input(input, first, "no start `S` found in the sketch").in_day(10)
+    })?;
+    Ok(Sketch { start, grid })
 }
 
-#[aoc_generator(day10)]
-fn parse(input: &str) -> Grid<140, 140> {
-    parse_grid(input)
+fn part1(sketch: &Sketch) -> usize {
+    sketch.find_steps()
 }
 
-#[aoc(day10, part1)]
-fn part1<const N: usize, const M: usize>(grid: &Grid<N, M>) -> usize {
-    grid.find_steps()
+fn part2(sketch: &Sketch) -> usize {
+    sketch.find_enclosed_possitions()
 }
 
-#[aoc(day10, part2)]
-fn part2<const N: usize, const M: usize>(grid: &Grid<N, M>) -> usize {
-    grid.find_enclosed_possitions()
-}
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(10, 1, |input| timed(input, parse, part1)),
+    Solution::new(10, 2, |input| timed(input, parse, part2)),
+];
 
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
+    use proptest::{prelude::*, sample::Index};
 
     use super::*;
+    use crate::{
+        direction::SignedCoord,
+        strategies::{interior, loop_cells, rectilinear_loop},
+    };
 
     static INPUT1_CLEAN: &str = indoc! {"
         .....
@@ -338,18 +301,113 @@ mod tests {
     #[test]
     fn test_parse() {
         for input in [INPUT1_CLEAN, INPUT1, INPUT2_CLEAN, INPUT2_CLEAN] {
-            parse_grid::<5, 5>(input);
+            assert_eq!(parse(input).unwrap().grid.size(), (5, 5));
         }
     }
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_grid::<5, 5>(INPUT1)), 4);
-        assert_eq!(part1(&parse_grid::<5, 5>(INPUT2)), 8);
+        assert_eq!(part1(&parse(INPUT1).unwrap()), 4);
+        assert_eq!(part1(&parse(INPUT2).unwrap()), 8);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_grid::<20, 10>(PART2_2)), 10);
+        assert_eq!(part2(&parse(PART2_2).unwrap()), 10);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse("..\n.x").err().unwrap();
+        assert_eq!(
+            (error.day, error.line, error.column),
+            (Some(10), Some(2), Some(2))
+        );
+
+        let error = parse("..\n..").err().unwrap();
+        assert_eq!((error.line, error.column), (Some(1), Some(1)));
+        assert_eq!(error.text, "..");
+    }
+
+    const MAX_SIZE: usize = 20;
+
+    /// Direction of the step from `a` to the adjacent `b`.
+    fn towards(a: SignedCoord, b: SignedCoord) -> Direction {
+        Direction::ALL
+            .into_iter()
+            .find(|dir| dir.steps(a, 1) == Some(b))
+            .unwrap()
+    }
+
+    fn pipe_char(mut ends: [Direction; 2]) -> char {
+        ends.sort();
+        match ends {
+            [North, South] => '|',
+            [North, East] => 'L',
+            [North, West] => 'J',
+            [South, East] => 'F',
+            [South, West] => '7',
+            [East, West] => '-',
+            _ => unreachable!("a pipe can't turn back"),
+        }
+    }
+
+    /// Sketches of a random loop with `S` somewhere on it, surrounded by junk
+    /// pipes that don't connect to `S`. Also returns the loop cells.
+    fn sketch() -> impl Strategy<Value = (String, Vec<SignedCoord>)> {
+        let junk = prop::collection::vec(
+            prop::sample::select(vec!['|', '-', 'L', 'J', '7', 'F', '.']),
+            MAX_SIZE * MAX_SIZE,
+        );
+        (rectilinear_loop(), junk, any::<Index>(), 0..3_isize).prop_map(
+            |(moves, junk, start, margin)| {
+                let cells = loop_cells(&moves);
+                let start = cells[start.index(cells.len())];
+                let min_x = cells.iter().map(|c| c.0).min().unwrap() - margin;
+                let min_y = cells.iter().map(|c| c.1).min().unwrap() - margin;
+                let max_x = cells.iter().map(|c| c.0).max().unwrap() + margin;
+                let max_y = cells.iter().map(|c| c.1).max().unwrap() + margin;
+                let mut grid =
+                    vec![vec!['.'; (max_x - min_x + 1) as usize]; (max_y - min_y + 1) as usize];
+                for (y, row) in grid.iter_mut().enumerate() {
+                    for (x, c) in row.iter_mut().enumerate() {
+                        let pos = (x as isize + min_x, y as isize + min_y);
+                        let next_to_start = Direction::ALL
+                            .into_iter()
+                            .any(|dir| dir.steps(start, 1) == Some(pos));
+                        if !next_to_start {
+                            *c = junk[y * MAX_SIZE + x];
+                        }
+                    }
+                }
+                for (i, &cell) in cells.iter().enumerate() {
+                    let before = cells[(i + cells.len() - 1) % cells.len()];
+                    let after = cells[(i + 1) % cells.len()];
+                    grid[(cell.1 - min_y) as usize][(cell.0 - min_x) as usize] = if cell == start {
+                        'S'
+                    } else {
+                        pipe_char([towards(cell, before), towards(cell, after)])
+                    };
+                }
+                let input = grid
+                    .iter()
+                    .map(String::from_iter)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                (input, cells)
+            },
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn farthest_is_half_the_loop((input, cells) in sketch()) {
+            prop_assert_eq!(part1(&parse(&input).unwrap()), cells.len() / 2);
+        }
+
+        #[test]
+        fn enclosed_by_picks_theorem((input, cells) in sketch()) {
+            prop_assert_eq!(part2(&parse(&input).unwrap()), interior(&cells));
+        }
     }
 }
@@ -1,11 +1,26 @@
-use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
-fn parse_distance(input: &str, distance: usize) -> Vec<(usize, usize)> {
+use crate::{
+    error::ParseError,
+    runner::{timed, Solution},
+};
+
+fn parse_distance(input: &str, distance: usize) -> Result<Vec<(usize, usize)>, ParseError> {
     let mut seen = Vec::<(usize, usize)>::new();
-    let mut x_seen = vec![false; input.lines().next().unwrap().len()];
+    let width = input.lines().next().map_or(0, |line| line.chars().count());
+    let mut x_seen = vec![false; width];
     let mut extra_lines = 0;
     for (y, line) in input.lines().enumerate() {
+        let len = line.chars().count();
+        if len != width {
+            return Err(ParseError::at(
+                line,
+                line,
+                format!("expected a row of {width} cells, found {len}"),
+            )
+            .on_line(y + 1)
+            .in_day(11));
+        }
         let mut items = 0;
         for (x, symbol) in line.char_indices() {
             match symbol {
@@ -15,7 +30,13 @@ fn parse_distance(input: &str, distance: usize) -> Vec<(usize, usize)> {
                     x_seen[x] = true;
                 }
                 '.' => (),
-                c => unreachable!("Unknown symbol: {}", c),
+                c => {
+                    return Err(
+                        ParseError::at(line, &line[x..x + c.len_utf8()], "unknown symbol")
+                            .on_line(y + 1)
+                            .in_day(11),
+                    )
+                }
             }
         }
         if items == 0 {
@@ -32,16 +53,14 @@ fn parse_distance(input: &str, distance: usize) -> Vec<(usize, usize)> {
                 .filter(|(x, _)| *x >= pos)
                 .for_each(|(x, _)| *x += distance - 1);
         });
-    seen
+    Ok(seen)
 }
 
-#[aoc_generator(day11, part1)]
-fn parse_p1(input: &str) -> Vec<(usize, usize)> {
+fn parse_p1(input: &str) -> Result<Vec<(usize, usize)>, ParseError> {
     parse_distance(input, 2)
 }
 
-#[aoc_generator(day11, part2)]
-fn parse_p2(input: &str) -> Vec<(usize, usize)> {
+fn parse_p2(input: &str) -> Result<Vec<(usize, usize)>, ParseError> {
     parse_distance(input, 1_000_000)
 }
 
@@ -58,16 +77,19 @@ fn shortest_path_every_pair(graph: &[(usize, usize)]) -> usize {
         .sum()
 }
 
-#[aoc(day11, part1)]
 fn part1(graph: &[(usize, usize)]) -> usize {
     shortest_path_every_pair(graph)
 }
 
-#[aoc(day11, part2)]
 fn part2(graph: &[(usize, usize)]) -> usize {
     shortest_path_every_pair(graph)
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(11, 1, |input| timed(input, parse_p1, part1)),
+    Solution::new(11, 2, |input| timed(input, parse_p2, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -92,16 +114,26 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_p1(INPUT)), 374);
+        assert_eq!(part1(&parse_p1(INPUT).unwrap()), 374);
     }
 
     #[test]
     fn part2_example_1() {
-        assert_eq!(part2(&parse_distance(INPUT, 10)), 1030);
+        assert_eq!(part2(&parse_distance(INPUT, 10).unwrap()), 1030);
     }
 
     #[test]
     fn part2_example_2() {
-        assert_eq!(part2(&parse_distance(INPUT, 100)), 8410);
+        assert_eq!(part2(&parse_distance(INPUT, 100).unwrap()), 8410);
+    }
+
+    #[test]
+    fn parse_error() {
+        let error = parse_p1("..#\n.o.").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(2)));
+
+        let error = parse_p1("..#\n.#.\n#...").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(3), Some(1)));
+        assert_eq!(error.reason, "expected a row of 3 cells, found 4");
     }
 }
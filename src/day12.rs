@@ -1,14 +1,14 @@
-use std::{
-    collections::HashMap,
-    iter::repeat_with,
-    num::ParseIntError,
-    str::{pattern::Pattern, FromStr},
-};
+use std::{collections::HashMap, iter::repeat_with, str::FromStr};
 
-use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use Field::*;
 
+use crate::{
+    error::{parse_lines, ParseError},
+    numbers,
+    runner::{timed, Solution},
+};
+
 #[derive(Debug, PartialEq, Eq, Hash)]
 struct SpringRow {
     row: Vec<Field>,
@@ -23,20 +23,26 @@ enum Field {
 }
 
 impl FromStr for SpringRow {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (s_row, numbers) = s.split_once(' ').unwrap();
+        let (s_row, numbers) = s
+            .split_once(' ')
+            .ok_or_else(|| ParseError::at(s, s, "expected `<springs> <groups>`"))?;
         let row = s_row
-            .chars()
-            .map(|c| match c {
-                '#' => Damaged,
-                '.' => Operational,
-                '?' => Unknown,
-                _ => unreachable!("Character is unknown: {}", c),
+            .char_indices()
+            .map(|(n, c)| match c {
+                '#' => Ok(Damaged),
+                '.' => Ok(Operational),
+                '?' => Ok(Unknown),
+                _ => Err(ParseError::at(
+                    s,
+                    &s_row[n..n + c.len_utf8()],
+                    "unknown spring",
+                )),
             })
-            .collect();
-        let numbers = str_numbers_to_collecatble(numbers, ',')?;
+            .collect::<Result<_, _>>()?;
+        let numbers = numbers::comma_separated(s, numbers)?;
         Ok(Self { row, numbers })
     }
 }
@@ -86,55 +92,37 @@ fn count(
     result
 }
 
-fn str_numbers_to_collecatble<'a, T, N, P>(s: &'a str, p: P) -> Result<T, ParseIntError>
-where
-    T: FromIterator<N>,
-    N: FromStr<Err = ParseIntError>,
-    P: Pattern<'a>,
-{
-    s.split(p)
-        .filter(|n| !n.is_empty())
-        .map(N::from_str)
-        .collect()
-}
-
-#[aoc_generator(day12, part1)]
-fn parse1(input: &str) -> Vec<SpringRow> {
-    input
-        .lines()
-        .map(SpringRow::from_str)
-        .try_collect()
-        .unwrap()
+fn parse1(input: &str) -> Result<Vec<SpringRow>, ParseError> {
+    parse_lines(12, input, SpringRow::from_str)
 }
 
-#[aoc_generator(day12, part2)]
-fn parse2(input: &str) -> Vec<SpringRow> {
-    let mut map: Vec<_> = input
-        .lines()
-        .map(SpringRow::from_str)
-        .try_collect()
-        .unwrap();
+fn parse2(input: &str) -> Result<Vec<SpringRow>, ParseError> {
+    let mut map = parse_lines(12, input, SpringRow::from_str)?;
     map.iter_mut().for_each(SpringRow::unfold);
-    map
+    Ok(map)
 }
 
-#[aoc(day12, part1)]
 fn part1(rows: &[SpringRow]) -> usize {
     rows.iter()
         .map(|c| count(&c.row, &c.numbers, &mut HashMap::new()))
         .sum()
 }
 
-#[aoc(day12, part2)]
 fn part2(rows: &[SpringRow]) -> usize {
     rows.iter()
         .map(|c| count(&c.row, &c.numbers, &mut HashMap::new()))
         .sum()
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(12, 1, |input| timed(input, parse1, part1)),
+    Solution::new(12, 2, |input| timed(input, parse2, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
+    use proptest::prelude::*;
 
     use super::*;
 
@@ -162,11 +150,86 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse1(INPUT)), 21);
+        assert_eq!(part1(&parse1(INPUT).unwrap()), 21);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse2(INPUT)), 525152);
+        assert_eq!(part2(&parse2(INPUT).unwrap()), 525152);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse1("???.### 1,1,3\n.??.x 1,1").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(5)));
+
+        let error = parse1("???.### 1,one,3").unwrap_err();
+        assert_eq!((error.column, error.text.as_str()), (Some(11), "one"));
+    }
+
+    /// Spring rows with at least one arrangement: the groups are taken from a
+    /// random row of damaged and operational springs, then some springs are
+    /// hidden.
+    fn spring_row() -> impl Strategy<Value = String> {
+        prop::collection::vec((any::<bool>(), any::<bool>()), 1..16)
+            .prop_filter("needs a damaged spring", |springs| {
+                springs.iter().any(|(damaged, _)| *damaged)
+            })
+            .prop_map(|springs| {
+                let row: String = springs
+                    .iter()
+                    .map(|&(damaged, hidden)| match (damaged, hidden) {
+                        (_, true) => '?',
+                        (true, false) => '#',
+                        (false, false) => '.',
+                    })
+                    .collect();
+                let groups = springs
+                    .iter()
+                    .group_by(|(damaged, _)| *damaged)
+                    .into_iter()
+                    .filter(|(damaged, _)| *damaged)
+                    .map(|(_, group)| group.count())
+                    .join(",");
+                format!("{row} {groups}")
+            })
+    }
+
+    /// Tries every way to fill in the unknown springs.
+    fn brute_force(row: &SpringRow) -> usize {
+        let unknown = row.row.iter().filter(|f| **f == Unknown).count();
+        (0..1_u32 << unknown)
+            .filter(|bits| {
+                let mut bit = 0;
+                let filled = row.row.iter().map(|&field| {
+                    if field != Unknown {
+                        return field;
+                    }
+                    bit += 1;
+                    if bits & (1 << (bit - 1)) == 0 {
+                        Operational
+                    } else {
+                        Damaged
+                    }
+                });
+                let groups = filled
+                    .group_by(|f| *f == Damaged)
+                    .into_iter()
+                    .filter(|(damaged, _)| *damaged)
+                    .map(|(_, group)| group.count())
+                    .collect_vec();
+                groups == row.numbers
+            })
+            .count()
+    }
+
+    proptest! {
+        #[test]
+        fn count_is_brute_force(line in spring_row()) {
+            let row: SpringRow = line.parse().unwrap();
+            let arrangements = count(&row.row, &row.numbers, &mut HashMap::new());
+            prop_assert!(arrangements >= 1);
+            prop_assert_eq!(arrangements, brute_force(&row));
+        }
     }
 }
@@ -1,23 +1,34 @@
 use std::str::FromStr;
 
-use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
 use Material::*;
 
+use crate::{
+    error::{lines_before, ParseError},
+    runner::{timed, Solution},
+};
+
 struct Grid {
     grid: Vec<Vec<Material>>,
 }
 
 impl FromStr for Grid {
-    type Err = ();
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let grid = s
             .lines()
-            .map(|line| line.chars().map(Material::try_from).try_collect())
-            .try_collect()
-            .unwrap();
+            .map(|line| {
+                line.char_indices()
+                    .map(|(n, c)| {
+                        Material::try_from(c).map_err(|c| {
+                            ParseError::in_input(s, &line[n..n + c.len_utf8()], "unknown material")
+                        })
+                    })
+                    .try_collect()
+            })
+            .try_collect()?;
         Ok(Self { grid })
     }
 }
@@ -43,7 +54,7 @@ impl Grid {
             centers.retain(|center| {
                 line_number
                     .checked_sub((line_number - center) * 2 + 1)
-                    .map_or(true, |i| self.grid[i] == *grid_line)
+                    .is_none_or(|i| self.grid[i] == *grid_line)
             });
             if line_number != 0 && self.grid[line_number - 1] == *grid_line {
                 centers.push(line_number);
@@ -127,25 +138,28 @@ impl TryFrom<char> for Material {
     }
 }
 
-#[aoc_generator(day13)]
-fn parse(input: &str) -> Vec<Grid> {
+fn parse(input: &str) -> Result<Vec<Grid>, ParseError> {
     input
         .split("\n\n")
-        .map(Grid::from_str)
+        .map(|block| {
+            Grid::from_str(block).map_err(|e| e.offset_lines(lines_before(input, block)).in_day(13))
+        })
         .try_collect()
-        .unwrap()
 }
 
-#[aoc(day13, part1)]
 fn part1(grid: &[Grid]) -> usize {
     grid.iter().map(Grid::mirror_score).sum()
 }
 
-#[aoc(day13, part2)]
 fn part2(grid: &[Grid]) -> usize {
     grid.iter().map(Grid::smugde_mirror_score).sum()
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(13, 1, |input| timed(input, parse, part1)),
+    Solution::new(13, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -172,11 +186,17 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(INPUT)), 405);
+        assert_eq!(part1(&parse(INPUT).unwrap()), 405);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(INPUT)), 400);
+        assert_eq!(part2(&parse(INPUT).unwrap()), 400);
+    }
+
+    #[test]
+    fn parse_error() {
+        let error = parse("#.\n.#\n\n##\n#o").err().unwrap();
+        assert_eq!((error.line, error.column), (Some(5), Some(2)));
     }
 }
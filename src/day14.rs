@@ -1,63 +1,23 @@
 use std::{
-    assert_matches::assert_matches,
     collections::{
         hash_map::Entry::{Occupied, Vacant},
-        BTreeMap, HashMap,
+        HashMap,
     },
-    fmt::{Debug, Display},
+    fmt::Display,
+    mem::replace,
 };
 
-use aoc_runner_derive::{aoc, aoc_generator};
-
 use indexmap::IndexMap;
-use itertools::Itertools;
 use Direction::*;
 use RockType::*;
 
-type GridMap = BTreeMap<(usize, usize), RockType>;
-
-#[derive(PartialEq, Eq, Hash, Clone)]
-struct Grid {
-    grid: GridMap,
-    size: (usize, usize),
-}
-
-impl Display for Grid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let grid = (0..self.size.1)
-            .map(|y| {
-                (0..self.size.0)
-                    .map(|x| {
-                        self.grid
-                            .get(&(x, y))
-                            .copied()
-                            .map_or('.', char::from)
-                    })
-                    .collect::<String>()
-            })
-            .join("\n");
-        writeln!(f, "\n{}", grid)
-    }
-}
-
-impl Debug for Grid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self)
-    }
-}
+use crate::{direction::Direction, error::ParseError, grid::Grid, runner::{timed, Solution}};
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 enum RockType {
     CubeRock,
     RoundRock,
-}
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
+    Empty,
 }
 
 impl TryFrom<char> for RockType {
@@ -67,8 +27,8 @@ impl TryFrom<char> for RockType {
         match c {
             'O' => Ok(RoundRock),
             '#' => Ok(CubeRock),
-            '.' => Err(c),
-            _ => unreachable!("Unknown rock: {}", c),
+            '.' => Ok(Empty),
+            _ => Err(c),
         }
     }
 }
@@ -78,32 +38,39 @@ impl From<RockType> for char {
         match value {
             CubeRock => '#',
             RoundRock => 'O',
+            Empty => '.',
         }
     }
 }
 
+impl Display for RockType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", char::from(*self))
+    }
+}
+
 impl Direction {
-    fn cross_direction_iter(&self, grid: &Grid) -> impl Iterator<Item = usize> {
+    fn cross_direction_iter(&self, grid: &Grid<RockType>) -> impl Iterator<Item = usize> {
         match self {
-            North | South => 0..grid.size.0,
-            East | West => 0..grid.size.1,
+            North | South => 0..grid.width(),
+            East | West => 0..grid.height(),
         }
     }
 
-    fn with_direction_iter(&self, grid: &Grid) -> Box<dyn Iterator<Item = usize>> {
+    fn with_direction_iter(&self, grid: &Grid<RockType>) -> Box<dyn Iterator<Item = usize>> {
         match self {
-            North => Box::new(0..grid.size.1),
-            South => Box::new((0..grid.size.1).rev()),
-            East => Box::new((0..grid.size.0).rev()),
-            West => Box::new(0..grid.size.0),
+            North => Box::new(0..grid.height()),
+            South => Box::new((0..grid.height()).rev()),
+            East => Box::new((0..grid.width()).rev()),
+            West => Box::new(0..grid.width()),
         }
     }
 
-    fn with_direction_start(&self, grid: &Grid) -> usize {
+    fn with_direction_start(&self, grid: &Grid<RockType>) -> usize {
         match self {
             North | West => 0,
-            South => grid.size.1 - 1,
-            East => grid.size.0 - 1,
+            South => grid.height() - 1,
+            East => grid.width() - 1,
         }
     }
 
@@ -122,49 +89,43 @@ impl Direction {
     }
 }
 
-impl Grid {
+impl Grid<RockType> {
     fn roll_direction(&self, direction: Direction) -> Self {
-        let mut rolled = GridMap::new();
+        let mut rolled = Grid::new(self.width(), self.height(), Empty);
         for cross_direction in direction.cross_direction_iter(self) {
             let mut next_avalible = direction.with_direction_start(self);
             for with_direction in direction.with_direction_iter(self) {
                 let coord = direction.cross_with_to_coord(cross_direction, with_direction);
-                if let Some(rock) = self.grid.get(&coord) {
-                    match rock {
-                        CubeRock => {
-                            assert_matches!(rolled.insert(coord, CubeRock), None);
-                            next_avalible =
-                                direction.with_direction_to_next_avalible(with_direction);
-                        }
-                        RoundRock => {
-                            assert_matches!(
-                                rolled.insert(
-                                    direction.cross_with_to_coord(cross_direction, next_avalible),
-                                    RoundRock
-                                ),
-                                None,
-                                "{:?}, {:?}",
-                                (cross_direction, next_avalible),
-                                direction
-                            );
-                            next_avalible =
-                                direction.with_direction_to_next_avalible(next_avalible);
-                        }
+                match self[coord] {
+                    CubeRock => {
+                        assert_matches!(replace(&mut rolled[coord], CubeRock), Empty);
+                        next_avalible = direction.with_direction_to_next_avalible(with_direction);
                     }
+                    RoundRock => {
+                        assert_matches!(
+                            replace(
+                                &mut rolled
+                                    [direction.cross_with_to_coord(cross_direction, next_avalible)],
+                                RoundRock
+                            ),
+                            Empty,
+                            "{:?}, {:?}",
+                            (cross_direction, next_avalible),
+                            direction
+                        );
+                        next_avalible = direction.with_direction_to_next_avalible(next_avalible);
+                    }
+                    Empty => (),
                 }
             }
         }
-        Self {
-            grid: rolled,
-            size: self.size,
-        }
+        rolled
     }
 
     fn total_load(&self) -> usize {
-        self.grid
-            .iter()
+        self.indexed_iter()
             .filter(|&(_, rock)| *rock == RoundRock)
-            .map(|(pos, _)| self.size.1 - pos.1)
+            .map(|(pos, _)| self.height() - pos.1)
             .sum()
     }
 
@@ -177,63 +138,48 @@ impl Grid {
     }
 
     fn total_load_shake(&self, cycles: usize) -> usize {
-        let mut cache: HashMap<Grid, usize> = HashMap::new();
-        let mut lookup: IndexMap<usize, Grid> = IndexMap::new();
+        let mut cache: HashMap<Self, usize> = HashMap::new();
+        let mut lookup: IndexMap<usize, Self> = IndexMap::new();
         let mut current: Self = self.clone();
-        let mut cycle = 0;
-        let mut found = 0;
-        for _ in 0..cycles {
+        for cycle in 0..cycles {
             match cache.entry(current.clone()) {
                 Vacant(vacant) => {
                     lookup.insert(cycle, vacant.key().clone());
                     vacant.insert(cycle);
                 }
                 Occupied(occupied) => {
-                    found = *occupied.get();
-                    break;
+                    let found = *occupied.get();
+                    let offset = (cycles - found) % (cycle - found) + found;
+                    return lookup.get(&offset).unwrap().total_load();
                 }
             }
             current = current.cycle();
-            cycle += 1;
         }
-        let offset = (cycles - found) % (cycle - found) + found;
-        lookup.get(&offset).unwrap().total_load()
+        current.total_load()
     }
 }
 
-#[aoc_generator(day14)]
-fn parse(input: &str) -> Grid {
-    let mut grid = GridMap::new();
-    let mut x_max = 0;
-    let mut y_max = 0;
-    for (y, line) in input.lines().enumerate() {
-        y_max = y_max.max(y + 1);
-        for (x, c) in line.char_indices() {
-            x_max = x_max.max(x + 1);
-            if let Ok(rock) = RockType::try_from(c) {
-                grid.insert((x, y), rock);
-            }
-        }
-    }
-    Grid {
-        grid,
-        size: (x_max, y_max),
-    }
+fn parse(input: &str) -> Result<Grid<RockType>, ParseError> {
+    Grid::parse_with(input, RockType::try_from).map_err(|e| e.in_day(14))
 }
 
-#[aoc(day14, part1)]
-fn part1(grid: &Grid) -> usize {
+fn part1(grid: &Grid<RockType>) -> usize {
     grid.roll_direction(North).total_load()
 }
 
-#[aoc(day14, part2)]
-fn part2(grid: &Grid) -> usize {
+fn part2(grid: &Grid<RockType>) -> usize {
     grid.total_load_shake(1_000_000_000)
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(14, 1, |input| timed(input, parse, part1)),
+    Solution::new(14, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
+    use proptest::prelude::*;
 
     use super::*;
 
@@ -265,16 +211,80 @@ mod tests {
 
     #[test]
     fn test_rolled() {
-        assert_eq!(&parse(INPUT).roll_direction(North), &parse(ROLLED))
+        assert_eq!(
+            &parse(INPUT).unwrap().roll_direction(North),
+            &parse(ROLLED).unwrap()
+        )
     }
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(INPUT)), 136);
+        assert_eq!(part1(&parse(INPUT).unwrap()), 136);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(INPUT)), 64);
+        assert_eq!(part2(&parse(INPUT).unwrap()), 64);
+    }
+
+    #[test]
+    fn test_shake_without_repeat() {
+        let grid = parse(INPUT).unwrap();
+        assert_eq!(grid.total_load_shake(1), grid.cycle().total_load());
+    }
+
+    #[test]
+    fn parse_error() {
+        let error = parse("O.#\n.0.").unwrap_err();
+        assert_eq!(
+            (error.day, error.line, error.column),
+            (Some(14), Some(2), Some(2))
+        );
+    }
+
+    /// Platforms of up to 8 by 8 rocks.
+    fn platform() -> impl Strategy<Value = String> {
+        (1..8_usize, 1..8_usize).prop_flat_map(|(width, height)| {
+            let line = prop::collection::vec(prop::sample::select(vec!['O', '#', '.']), width)
+                .prop_map(String::from_iter);
+            prop::collection::vec(line, height).prop_map(|lines| lines.join("\n"))
+        })
+    }
+
+    /// Moves round rocks one step at a time until none can move.
+    fn roll_step_by_step(grid: &Grid<RockType>, direction: Direction) -> Grid<RockType> {
+        let mut grid = grid.clone();
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for coord in grid.coords().collect::<Vec<_>>() {
+                let Some(next) = direction.step(coord, grid.size()) else {
+                    continue;
+                };
+                if grid[coord] == RoundRock && grid[next] == Empty {
+                    grid[coord] = Empty;
+                    grid[next] = RoundRock;
+                    moved = true;
+                }
+            }
+        }
+        grid
+    }
+
+    proptest! {
+        #[test]
+        fn roll_is_step_by_step(input in platform()) {
+            let grid = parse(&input).unwrap();
+            for direction in Direction::ALL {
+                prop_assert_eq!(grid.roll_direction(direction), roll_step_by_step(&grid, direction));
+            }
+        }
+
+        #[test]
+        fn shake_skips_repeated_cycles(input in platform(), cycles in 1..40_usize) {
+            let grid = parse(&input).unwrap();
+            let shaken = (0..cycles).fold(grid.clone(), |grid, _| grid.cycle());
+            prop_assert_eq!(grid.total_load_shake(cycles), shaken.total_load());
+        }
     }
 }
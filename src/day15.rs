@@ -1,4 +1,7 @@
-use aoc_runner_derive::{aoc, aoc_generator};
+use crate::{
+    error::ParseError,
+    runner::{timed, Solution},
+};
 
 type Hashmap<'a> = Vec<Vec<(&'a str, usize)>>;
 
@@ -7,40 +10,71 @@ fn hash_string(s: &str) -> u8 {
         .fold(0usize, |acc, c| (acc + c as usize) * 17 % 256) as u8
 }
 
-#[aoc_generator(day15)]
-fn parse(input: &str) -> Vec<String> {
-    input.split(',').map(str::to_owned).collect()
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operation {
+    Remove,
+    Set(usize),
 }
 
-#[aoc(day15, part1)]
-fn part1(input: &[String]) -> usize {
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    /// The whole step, which part 1 hashes.
+    text: String,
+    label: String,
+    operation: Operation,
+}
+
+impl Step {
+    /// Parses `step`, which has to be a slice of `input`.
+    fn parse(input: &str, step: &str) -> Result<Self, ParseError> {
+        let (label, operation) = if let Some(label) = step.strip_suffix('-') {
+            (label, Operation::Remove)
+        } else if let Some((label, value)) = step.split_once('=') {
+            let value = value
+                .parse()
+                .map_err(|e| ParseError::in_input(input, value, format!("{e}")))?;
+            (label, Operation::Set(value))
+        } else {
+            let reason = "expected `<label>-` or `<label>=<focal length>`";
+            return Err(ParseError::in_input(input, step, reason));
+        };
+        Ok(Self {
+            text: step.to_owned(),
+            label: label.to_owned(),
+            operation,
+        })
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<Step>, ParseError> {
     input
+        .split(',')
+        .map(|step| Step::parse(input, step).map_err(|e| e.in_day(15)))
+        .collect()
+}
+
+fn part1(steps: &[Step]) -> usize {
+    steps
         .iter()
-        .map(|s| hash_string(s))
+        .map(|step| hash_string(&step.text))
         .map(Into::<usize>::into)
         .sum()
 }
 
-#[aoc(day15, part2)]
-fn part2(input: &[String]) -> usize {
+fn part2(steps: &[Step]) -> usize {
     let mut map: Hashmap = vec![Vec::new(); 256];
-    for i in input {
-        if i.contains('-') {
-            let key = &i[..i.len() - 1];
-            let hash = hash_string(key) as usize;
-            map.get_mut(hash).unwrap().retain(|n| n.0 != key);
-        } else if i.contains('=') {
-            let (key, value) = i.split_once('=').unwrap();
-            let value: usize = value.parse().unwrap();
-            let hash = hash_string(key) as usize;
-            let bucket = map.get_mut(hash).unwrap();
-            if let Some(entry) = bucket.iter_mut().find(|n| n.0 == key) {
-                entry.1 = value;
-            } else {
-                bucket.push((key, value));
+    for step in steps {
+        let key = step.label.as_str();
+        let bucket = &mut map[hash_string(key) as usize];
+        match step.operation {
+            Operation::Remove => bucket.retain(|n| n.0 != key),
+            Operation::Set(value) => {
+                if let Some(entry) = bucket.iter_mut().find(|n| n.0 == key) {
+                    entry.1 = value;
+                } else {
+                    bucket.push((key, value));
+                }
             }
-        } else {
-            unreachable!("Unknown instruction: {i}")
         }
     }
     map.into_iter()
@@ -54,6 +88,11 @@ fn part2(input: &[String]) -> usize {
         .sum()
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(15, 1, |input| timed(input, parse, part1)),
+    Solution::new(15, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,11 +106,24 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(INPUT)), 1320);
+        assert_eq!(part1(&parse(INPUT).unwrap()), 1320);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(INPUT)), 145);
+        assert_eq!(part2(&parse(INPUT).unwrap()), 145);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse("rn=1,cm+,qp=3").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(1), Some(6)));
+        assert_eq!((error.text.as_str(), error.day), ("cm+", Some(15)));
+
+        let error = parse("rn=1,cm=x").unwrap_err();
+        assert_eq!((error.column, error.text.as_str()), (Some(9), "x"));
+
+        let error = parse("32T3K 765").unwrap_err();
+        assert_eq!(error.column, Some(1));
     }
 }
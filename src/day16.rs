@@ -1,79 +1,27 @@
 use std::{
     collections::{HashMap, HashSet},
-    fmt::{Debug, Display},
-    ops::{Index, IndexMut},
-    str::FromStr,
+    fmt::Display,
 };
 
-use aoc_runner_derive::{aoc, aoc_generator};
-
 use itertools::Itertools;
 use Direction::*;
 use Tile::*;
 
-type Seen = HashMap<(usize, usize), HashSet<Direction>>;
-
-struct Grid<const N: usize> {
-    grid: [[Tile; N]; N],
-}
-
-impl<const N: usize> Display for Grid<N> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let grid = self
-            .grid
-            .iter()
-            .map(|line| {
-                line.iter()
-                    .map(|tile| char::from(*tile))
-                    .collect::<String>()
-            })
-            .join("\n");
-        writeln!(f, "\n{}", grid)
-    }
-}
-
-impl<const N: usize> Debug for Grid<N> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self)
-    }
-}
-
-impl<const N: usize> Index<(usize, usize)> for Grid<N> {
-    type Output = Tile;
-
-    fn index(&self, index: (usize, usize)) -> &Self::Output {
-        self.grid.index(index.1).index(index.0)
-    }
-}
-
-impl<const N: usize> IndexMut<(usize, usize)> for Grid<N> {
-    fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
-        self.grid.index_mut(index.1).index_mut(index.0)
-    }
-}
-
-impl<const N: usize> FromStr for Grid<N> {
-    type Err = ();
+use crate::{
+    direction::{Direction, Mirror},
+    error::ParseError,
+    grid::{Coord, Grid},
+    runner::{timed, Solution},
+};
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut grid = Self {
-            grid: [[Empty; N]; N],
-        };
-        for (y, line) in s.lines().enumerate() {
-            for (x, c) in line.char_indices() {
-                grid[(x, y)] = Tile::try_from(c).unwrap();
-            }
-        }
-        Ok(grid)
-    }
-}
+type Seen = HashMap<(usize, usize), HashSet<Direction>>;
 
-impl<const N: usize> Grid<N> {
+impl Grid<Tile> {
     #[allow(unused)]
     fn display_lighted_up(&self, seen: &Seen) -> String {
-        (0..self.grid.len())
+        (0..self.height())
             .map(|y| {
-                (0..self.grid[0].len())
+                (0..self.width())
                     .map(|x| match seen.get(&(x, y)) {
                         Some(_) => '#',
                         None => '.',
@@ -92,7 +40,7 @@ impl<const N: usize> Grid<N> {
     fn light_up_all(&self) -> usize {
         [North, East, West, South]
             .into_iter()
-            .flat_map(|dir| dir.edge_iterator::<N>())
+            .flat_map(|dir| dir.edge_iterator(self.size()))
             .map(|(pos, dir)| {
                 let mut seen = HashMap::new();
                 self.recurse_light_up(pos, dir, &mut seen);
@@ -113,26 +61,12 @@ impl<const N: usize> Grid<N> {
         }
         match self[possition] {
             Empty => (),
-            MirrorNorthEast => {
-                direction = match direction {
-                    North => East,
-                    South => West,
-                    East => North,
-                    West => South,
-                };
-            }
-            MirrorNorthWest => {
-                direction = match direction {
-                    North => West,
-                    South => East,
-                    East => South,
-                    West => North,
-                };
-            }
+            MirrorNorthEast => direction = direction.reflect(Mirror::Slash),
+            MirrorNorthWest => direction = direction.reflect(Mirror::Backslash),
             SplitterHorisontal => {
                 direction = match direction {
                     North | South => {
-                        if let Some(step) = West.step::<N>(possition) {
+                        if let Some(step) = West.step(possition, self.size()) {
                             self.recurse_light_up(step, West, seen);
                         }
                         East
@@ -143,7 +77,7 @@ impl<const N: usize> Grid<N> {
             SplitterVertical => {
                 direction = match direction {
                     East | West => {
-                        if let Some(step) = North.step::<N>(possition) {
+                        if let Some(step) = North.step(possition, self.size()) {
                             self.recurse_light_up(step, North, seen);
                         }
                         South
@@ -152,7 +86,7 @@ impl<const N: usize> Grid<N> {
                 };
             }
         }
-        if let Some(step) = direction.step::<N>(possition) {
+        if let Some(step) = direction.step(possition, self.size()) {
             self.recurse_light_up(step, direction, seen);
         }
     }
@@ -182,6 +116,12 @@ impl TryFrom<char> for Tile {
     }
 }
 
+impl Display for Tile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", char::from(*self))
+    }
+}
+
 impl From<Tile> for char {
     fn from(value: Tile) -> Self {
         match value {
@@ -194,67 +134,35 @@ impl From<Tile> for char {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
-}
-
 impl Direction {
-    fn step<const N: usize>(&self, pos: (usize, usize)) -> Option<(usize, usize)> {
-        match self {
-            North => Some((pos.0, pos.1.checked_sub(1)?)),
-            South => {
-                let added = pos.1 + 1;
-                if added < N {
-                    Some((pos.0, added))
-                } else {
-                    None
-                }
-            }
-            East => {
-                let added = pos.0 + 1;
-                if added < N {
-                    Some((added, pos.1))
-                } else {
-                    None
-                }
-            }
-            West => Some((pos.0.checked_sub(1)?, pos.1)),
-        }
-    }
-
-    fn edge_iterator<const N: usize>(&self) -> Box<dyn Iterator<Item = ((usize, usize), Self)>> {
+    fn edge_iterator(&self, size: Coord) -> Box<dyn Iterator<Item = ((usize, usize), Self)>> {
+        let (width, height) = size;
         match self {
-            North => Box::new((0..N).map(|n| ((n, N - 1), North))),
-            South => Box::new((0..N).map(|n| ((n, 0), South))),
-            East => Box::new((0..N).map(|n| ((0, n), East))),
-            West => Box::new((0..N).map(|n| ((N - 1, n), West))),
+            North => Box::new((0..width).map(move |n| ((n, height - 1), North))),
+            South => Box::new((0..width).map(|n| ((n, 0), South))),
+            East => Box::new((0..height).map(|n| ((0, n), East))),
+            West => Box::new((0..height).map(move |n| ((width - 1, n), West))),
         }
     }
 }
 
-fn parse_grid<const N: usize>(input: &str) -> Grid<N> {
-    input.parse().unwrap()
+fn parse(input: &str) -> Result<Grid<Tile>, ParseError> {
+    Grid::parse_with(input, Tile::try_from).map_err(|e| e.in_day(16))
 }
 
-#[aoc_generator(day16)]
-fn parse(input: &str) -> Grid<110> {
-    parse_grid(input)
-}
-
-#[aoc(day16, part1)]
-fn part1<const N: usize>(grid: &Grid<N>) -> usize {
+fn part1(grid: &Grid<Tile>) -> usize {
     grid.light_up()
 }
 
-#[aoc(day16, part2)]
-fn part2<const N: usize>(grid: &Grid<N>) -> usize {
+fn part2(grid: &Grid<Tile>) -> usize {
     grid.light_up_all()
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(16, 1, |input| timed(input, parse, part1)),
+    Solution::new(16, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -276,11 +184,18 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_grid::<10>(INPUT)), 46);
+        assert_eq!(part1(&parse(INPUT).unwrap()), 46);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_grid::<10>(INPUT)), 51);
+        assert_eq!(part2(&parse(INPUT).unwrap()), 51);
+    }
+
+    #[test]
+    fn part1_not_square() {
+        let grid = parse(".|.\n...\n").unwrap();
+        assert_eq!(grid.size(), (3, 2));
+        assert_eq!(part1(&grid), 3);
     }
 }
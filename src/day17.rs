@@ -1,107 +1,17 @@
-use std::{cmp::Reverse, collections::{BinaryHeap, HashSet}, fmt::{Debug, Display}, ops::{Index, IndexMut, Not, RangeBounds}};
-use itertools::Itertools;
+use std::{cmp::Reverse, collections::{BinaryHeap, HashSet}, ops::RangeBounds};
 
-use aoc_runner_derive::{aoc, aoc_generator};
+use crate::{direction::Direction, error::ParseError, grid::{Coord, Grid}, runner::{timed, Solution}};
 
-type Coord = (usize, usize);
-
-#[derive(Clone)]
-struct Grid<const N: usize> {
-    grid: [[usize; N]; N],
-}
-
-impl<const N: usize> Grid<N> {
+impl Grid<usize> {
     fn end(&self) -> Coord {
-        (N - 1, N - 1)
-    }
-}
-
-impl<const N: usize> Index<Coord> for Grid<N> {
-    type Output = usize;
-
-    fn index(&self, index: Coord) -> &Self::Output {
-        self.grid.index(index.1).index(index.0)
-    }
-}
-
-impl<const N: usize> IndexMut<Coord> for Grid<N> {
-    fn index_mut(&mut self, index: Coord) -> &mut Self::Output {
-        self.grid.index_mut(index.1).index_mut(index.0)
-    }
-}
-
-impl<const N: usize> Display for Grid<N> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.grid.iter().map(|line| line.iter().copied().join("")).join("\n"))
-    }
-}
-
-impl<const N: usize> Debug for Grid<N> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        std::fmt::Display::fmt(self, f)
-    }
-}
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
-}
-
-fn checked_add_to_max(a: usize, b: usize, limit: usize) -> Option<usize> {
-    let ans = a + b;
-    (..limit).contains(&ans).then_some(ans)
-}
-
-impl Direction {
-    fn step(&self, coord: Coord, limit: usize) -> Option<Coord> {
-        Some(match self {
-            North => (coord.0, coord.1.checked_sub(1)?),
-            South => (coord.0, checked_add_to_max(coord.1, 1, limit)?),
-            East => (checked_add_to_max(coord.0, 1, limit)?, coord.1),
-            West => (coord.0.checked_sub(1)?, coord.1),
-        })
-    }
-}
-
-impl Not for Direction {
-    type Output = Direction;
-
-    fn not(self) -> Self::Output {
-        match self {
-            North => South,
-            South => North,
-            East => West,
-            West => East,
-        }
+        (self.width() - 1, self.height() - 1)
     }
 }
 
 use Direction::*;
 
-fn coords_iterator(n: usize) -> impl Iterator<Item=Coord> {
-    (0..n).flat_map(move |i| (0..n).map(move |j| (j, i)))
-}
-
-fn parse_grid<const N: usize>(input: &str) -> Grid<N> {
-    let mut grid = Grid::<N> {
-        grid: [[usize::MAX; N]; N],
-    };
-    for (number, coord) in input
-        .lines()
-        .flat_map(str::chars)
-        .map(|c| c.to_digit(10).unwrap() as usize)
-        .zip(coords_iterator(N))
-    {
-        grid[coord] = number;
-    }
-    grid
-}
-
-fn possible_moves_by_heat_loss(coord: Coord, direction: Direction, steps_in_direction: usize, limit: usize) -> Vec<(Coord, Direction)> {
-    [North, South, East, West]
+fn possible_moves_by_heat_loss(coord: Coord, direction: Direction, steps_in_direction: usize, limit: Coord) -> Vec<(Coord, Direction)> {
+    Direction::ALL
         .into_iter()
         .filter(move |d| *d != !direction)
         .filter(move |d| *d != direction || steps_in_direction < 3)
@@ -109,8 +19,8 @@ fn possible_moves_by_heat_loss(coord: Coord, direction: Direction, steps_in_dire
         .collect()
 }
 
-fn possible_moves_by_ultra_cruciblescrucibles(coord: Coord, direction: Direction, steps_in_direction: usize, limit: usize) -> Vec<(Coord, Direction)> {
-    [North, South, East, West]
+fn possible_moves_by_ultra_cruciblescrucibles(coord: Coord, direction: Direction, steps_in_direction: usize, limit: Coord) -> Vec<(Coord, Direction)> {
+    Direction::ALL
         .into_iter()
         .filter(move |d| *d != !direction)
         .filter(move |d| (*d == direction && steps_in_direction < 10) || (*d != direction && (4..=10).contains(&steps_in_direction)))
@@ -130,7 +40,7 @@ impl QueueOrder {
 
 }
 
-fn find_shortest_path<const N: usize, F: Fn(Coord, Direction, usize, usize) -> Vec<(Coord, Direction)>, R: RangeBounds<usize>>(grid: &Grid<N>, moves: F, steps_bounds: R) -> usize {
+fn find_shortest_path<F: Fn(Coord, Direction, usize, Coord) -> Vec<(Coord, Direction)>, R: RangeBounds<usize>>(grid: &Grid<usize>, moves: F, steps_bounds: R) -> usize {
     let mut seen = HashSet::<(Coord, Direction, usize)>::new();
     let mut queue = BinaryHeap::new();
     queue.push(Reverse(QueueOrder::new(0, (0, 0), South, 0)));
@@ -144,7 +54,7 @@ fn find_shortest_path<const N: usize, F: Fn(Coord, Direction, usize, usize) -> V
         if !seen.insert((tgt.pos, tgt.direction, tgt.num_steps)) {
             continue;
         }
-        let m = moves(tgt.pos, tgt.direction, tgt.num_steps, N);
+        let m = moves(tgt.pos, tgt.direction, tgt.num_steps, grid.size());
         for (new_pos, new_direction) in m {
             queue.push(Reverse(QueueOrder::new(tgt.cost + grid[new_pos], new_pos, new_direction, if tgt.direction == new_direction {tgt.num_steps + 1} else {1})));
         }
@@ -152,18 +62,15 @@ fn find_shortest_path<const N: usize, F: Fn(Coord, Direction, usize, usize) -> V
     unreachable!()
 }
 
-#[aoc_generator(day17)]
-fn parse(input: &str) -> Grid<141> {
-    parse_grid(input)
+fn parse(input: &str) -> Result<Grid<usize>, ParseError> {
+    Grid::parse_with(input, |c| c.to_digit(10).map(|n| n as usize).ok_or(c)).map_err(|e| e.in_day(17))
 }
 
-#[aoc(day17, part1)]
-fn part1<const N: usize>(grid: &Grid<N>) -> usize {
+fn part1(grid: &Grid<usize>) -> usize {
     find_shortest_path(grid, possible_moves_by_heat_loss, 1..=3)
 }
 
-#[aoc(day17, part2)]
-fn part2<const N: usize>(grid: &Grid<N>) -> usize {
+fn part2(grid: &Grid<usize>) -> usize {
     find_shortest_path(grid, possible_moves_by_ultra_cruciblescrucibles, 4..=10)
 }
 
@@ -175,6 +82,11 @@ struct QueueOrder {
     num_steps: usize,
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(17, 1, |input| timed(input, parse, part1)),
+    Solution::new(17, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -199,13 +111,13 @@ mod tests {
 
     #[test]
     fn test_parse() {
-        assert_eq!(INPUT.trim_end(), parse_grid::<13>(INPUT).to_string())
+        assert_eq!(INPUT.trim_end(), parse(INPUT).unwrap().to_string())
     }
 
     #[test]
     fn test_step() {
-        let grid: Grid<13> = parse_grid::<13>(INPUT);
-        let step = East.step((0, 0), 13).unwrap();
+        let grid = parse(INPUT).unwrap();
+        let step = East.step((0, 0), grid.size()).unwrap();
         assert_eq!(step, (1, 0));
         assert_eq!(grid[step], 4)
     }
@@ -213,18 +125,30 @@ mod tests {
     #[test]
     fn test_parse_full_input() {
         let input = std::fs::read_to_string("input/2023/day17.txt").unwrap();
-        assert_eq!(input.trim_end(), parse(&input).to_string())
+        assert_eq!(input.trim_end(), parse(&input).unwrap().to_string())
     }
 
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_grid::<13>(INPUT)), 102);
+        assert_eq!(part1(&parse(INPUT).unwrap()), 102);
     }
 
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_grid::<13>(INPUT)), 94);
+        assert_eq!(part2(&parse(INPUT).unwrap()), 94);
+    }
+
+    #[test]
+    fn part2_example_not_square() {
+        let input = indoc! {"
+            111111111111
+            999999999991
+            999999999991
+            999999999991
+            999999999991
+        "};
+        assert_eq!(part2(&parse(input).unwrap()), 71);
     }
 }
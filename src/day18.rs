@@ -1,9 +1,11 @@
-use std::ops::Not;
-
-use aoc_runner_derive::{aoc, aoc_generator};
-
 use nom::{
-    bytes::complete::{tag, take_while_m_n}, character::complete::{self, one_of}, combinator::map_res, multi::separated_list1, sequence::{delimited, pair, Tuple}, Finish, IResult, Parser
+    bytes::complete::{tag, take_while_m_n}, character::complete::{self, one_of}, combinator::map_res, multi::separated_list1, sequence::{delimited, pair, Tuple}, IResult, Parser
+};
+
+use crate::{
+    direction::{Direction, SignedCoord},
+    error::{finish_nom, ParseError},
+    runner::{timed, Solution},
 };
 
 #[derive(Debug, PartialEq, Eq)]
@@ -49,11 +51,11 @@ fn hex_color(input: &str) -> IResult<&str, Color> {
 fn direction(input: &str) -> IResult<&str, Direction> {
     let (input, letter) = one_of("UDLR0123")(input)?;
     let dir = match letter {
-        'U' | '3' => North,
-        'D' | '1' => South,
-        'L' | '2' => West,
-        'R' | '0' => East,
-        _ => unreachable!(),
+        '3' => North,
+        '1' => South,
+        '2' => West,
+        '0' => East,
+        c => Direction::try_from(c).unwrap(),
     };
     Ok((input, dir))
 }
@@ -80,7 +82,7 @@ fn parse_line_part2(input: &str) -> IResult<&str, Digg> {
     }))
 }
 
-type Coord = (isize, isize);
+type Coord = SignedCoord;
 
 #[derive(Debug, PartialEq, Eq)]
 struct Digg {
@@ -89,51 +91,11 @@ struct Digg {
     //color: Color,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum Direction {
-    North,
-    South,
-    East,
-    West,
-}
-
-impl Direction {
-    #[allow(dead_code)]
-    fn step(&self, coord: Coord) -> Option<Coord> {
-        Some(match self {
-            North => (coord.0, coord.1.checked_sub(1)?),
-            South => (coord.0, coord.1.checked_add(1)?),
-            East => (coord.0.checked_add(1)?, coord.1),
-            West => (coord.0.checked_sub(1)?, coord.1),
-        })
-    }
-
-    fn steps(&self, coord: Coord, len: isize) -> Option<Coord> {
-        Some(match self {
-            North => (coord.0, coord.1.checked_sub(len)?),
-            South => (coord.0, coord.1.checked_add(len)?),
-            East => (coord.0.checked_add(len)?, coord.1),
-            West => (coord.0.checked_sub(len)?, coord.1),
-        })
-    }
-}
-
-impl Not for Direction {
-    type Output = Direction;
-
-    fn not(self) -> Self::Output {
-        match self {
-            North => South,
-            South => North,
-            East => West,
-            West => East,
-        }
-    }
-}
-
 use Direction::*;
 
-fn dig_and_fill(diggs: &[Digg]) -> usize {
+/// Number of cells dug out, `None` if the trench doesn't enclose an area,
+/// like one that runs back over itself.
+fn dig_and_fill(diggs: &[Digg]) -> Option<usize> {
     let mut trenches = Vec::<Coord>::new();
     let mut pos = (0, 0);
     let mut trenches_len = 0;
@@ -143,8 +105,25 @@ fn dig_and_fill(diggs: &[Digg]) -> usize {
         trenches.push(pos);
         trenches_len += digg.length;
     }
-    let area = (trenches.windows(2).map(|a| a[0].0 * a[1].1 - a[0].1 * a[1].0).sum::<isize>()) as f64 / 2.;
-    (area - ((trenches_len as f64) * 0.5)) as usize + 1 + trenches_len
+    // Pick's theorem, the trench may be dug either way round
+    let twice_area = trenches.windows(2).map(|a| a[0].0 * a[1].1 - a[0].1 * a[1].0).sum::<isize>().unsigned_abs();
+    Some((twice_area + 2).checked_sub(trenches_len)? / 2 + trenches_len)
+}
+
+/// Checks that the trench of `diggs`, one per line of `input`, ends where it
+/// started and encloses an area.
+fn check_plan(input: &str, diggs: Vec<Digg>) -> Result<Vec<Digg>, ParseError> {
+    let end = diggs
+        .iter()
+        .try_fold((0, 0), |pos, digg| digg.direction.steps(pos, digg.length as isize));
+    let last = input.lines().last().unwrap_or(input);
+    if end != Some((0, 0)) {
+        return Err(ParseError::in_input(input, last, "the trench doesn't end where it started").in_day(18));
+    }
+    if dig_and_fill(&diggs).is_none() {
+        return Err(ParseError::in_input(input, last, "the trench doesn't enclose an area").in_day(18));
+    }
+    Ok(diggs)
 }
 
 fn parse_nom_part1(input: &str) -> IResult<&str, Vec<Digg>> {
@@ -155,24 +134,22 @@ fn parse_nom_part2(input: &str) -> IResult<&str, Vec<Digg>> {
     separated_list1(tag("\n"), parse_line_part2)(input)
 }
 
-#[aoc_generator(day18, part1)]
-fn parse(input: &str) -> Vec<Digg> {
-    parse_nom_part1(input).finish().unwrap().1
+fn parse(input: &str) -> Result<Vec<Digg>, ParseError> {
+    let diggs = finish_nom(input, parse_nom_part1(input)).map_err(|e| e.in_day(18))?;
+    check_plan(input, diggs)
 }
 
-#[aoc(day18, part1)]
 fn part1(diggs: &[Digg]) -> usize {
-    dig_and_fill(diggs)
+    dig_and_fill(diggs).expect("the plan is checked when parsing")
 }
 
-#[aoc_generator(day18, part2)]
-fn parse_part2(input: &str) -> Vec<Digg> {
-    parse_nom_part2(input).finish().unwrap().1
+fn parse_part2(input: &str) -> Result<Vec<Digg>, ParseError> {
+    let diggs = finish_nom(input, parse_nom_part2(input)).map_err(|e| e.in_day(18))?;
+    check_plan(input, diggs)
 }
 
-#[aoc(day18, part2)]
 fn part2(diggs: &[Digg]) -> usize {
-    dig_and_fill(diggs)
+    dig_and_fill(diggs).expect("the plan is checked when parsing")
 }
 
 // #[aoc(day17, part2)]
@@ -181,10 +158,18 @@ fn part2(diggs: &[Digg]) -> usize {
 // }
 
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(18, 1, |input| timed(input, parse, part1)),
+    Solution::new(18, 2, |input| timed(input, parse_part2, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::strategies::{loop_cells, rectilinear_loop};
     use indoc::indoc;
+    use proptest::prelude::*;
+    use std::collections::HashSet;
 
     static INPUT: &str = indoc! {r"
         R 6 (#70c710)
@@ -206,7 +191,7 @@ mod tests {
 
     #[test]
     fn test_parse_part1() {
-        let parsed = parse(INPUT);
+        let parsed = parse(INPUT).unwrap();
         assert_eq!(parsed.len(), 14);
         let first = Digg {
             direction: East,
@@ -218,7 +203,7 @@ mod tests {
 
     #[test]
     fn test_parse_part2() {
-        let parsed = parse_part2(INPUT);
+        let parsed = parse_part2(INPUT).unwrap();
         assert_eq!(parsed.len(), 14);
         let first = Digg {
             direction: East,
@@ -230,11 +215,93 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(INPUT)), 62);
+        assert_eq!(part1(&parse(INPUT).unwrap()), 62);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_part2(INPUT)), 952408144115);
+        assert_eq!(part2(&parse_part2(INPUT).unwrap()), 952408144115);
+    }
+
+    #[test]
+    fn test_counter_clockwise() {
+        let plan = "R 3 (#000030)\nU 1 (#000013)\nL 3 (#000032)\nD 1 (#000011)";
+        assert_eq!(part1(&parse(plan).unwrap()), 8);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse("R 6 (#70c710)\nX 5 (#0dc571)").unwrap_err();
+        assert_eq!((error.day, error.line, error.column), (Some(18), Some(2), Some(1)));
+
+        let error = parse_part2("R 6 (#70c710)\nD 5 (#0dc57)").unwrap_err();
+        assert_eq!(error.line, Some(2));
+    }
+
+    #[test]
+    fn degenerate_plans() {
+        let error = parse("R 3 (#000030)\nL 3 (#000032)").unwrap_err();
+        assert_eq!((error.line, error.reason.as_str()), (Some(2), "the trench doesn't enclose an area"));
+
+        let error = parse("R 3 (#000030)\nD 3 (#000031)").unwrap_err();
+        assert_eq!((error.line, error.reason.as_str()), (Some(2), "the trench doesn't end where it started"));
+
+        let error = parse_part2("R 1 (#000030)\nD 1 (#000032)").unwrap_err();
+        assert_eq!(error.reason, "the trench doesn't enclose an area");
+    }
+
+    /// Dig plans of random loops, the colors encode the same plan for part 2.
+    fn dig_plan() -> impl Strategy<Value = (Vec<(Direction, usize)>, String)> {
+        rectilinear_loop().prop_map(|moves| {
+            let plan = moves
+                .iter()
+                .map(|&(direction, length)| {
+                    let (c, digit) = match direction {
+                        East => ('R', 0),
+                        South => ('D', 1),
+                        West => ('L', 2),
+                        North => ('U', 3),
+                    };
+                    format!("{c} {length} (#{length:05x}{digit})")
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            (moves, plan)
+        })
+    }
+
+    /// Counts the trench and everything it encloses by flood filling the
+    /// outside of its bounding box.
+    fn flood_fill(moves: &[(Direction, usize)]) -> usize {
+        let trench: HashSet<Coord> = loop_cells(moves).into_iter().collect();
+        let (min_x, max_x) = trench.iter().fold((0, 0), |(lo, hi), c| (lo.min(c.0 - 1), hi.max(c.0 + 1)));
+        let (min_y, max_y) = trench.iter().fold((0, 0), |(lo, hi), c| (lo.min(c.1 - 1), hi.max(c.1 + 1)));
+        let mut outside = HashSet::from([(min_x, min_y)]);
+        let mut stack = vec![(min_x, min_y)];
+        while let Some(pos) = stack.pop() {
+            for direction in Direction::ALL {
+                let next = direction.steps(pos, 1).unwrap();
+                if (min_x..=max_x).contains(&next.0)
+                    && (min_y..=max_y).contains(&next.1)
+                    && !trench.contains(&next)
+                    && outside.insert(next)
+                {
+                    stack.push(next);
+                }
+            }
+        }
+        ((max_x - min_x + 1) * (max_y - min_y + 1)) as usize - outside.len()
+    }
+
+    proptest! {
+        #[test]
+        fn dig_and_fill_is_flood_fill((moves, plan) in dig_plan()) {
+            prop_assert_eq!(part1(&parse(&plan).unwrap()), flood_fill(&moves));
+        }
+
+        #[test]
+        fn colors_encode_the_plan((_, plan) in dig_plan()) {
+            prop_assert_eq!(parse_part2(&plan).unwrap(), parse(&plan).unwrap());
+        }
     }
 }
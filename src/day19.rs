@@ -1,20 +1,25 @@
-use std::{cmp::{max, Ordering}, collections::HashMap, iter::empty, mem::swap, ops::Range, sync::atomic::Ordering};
+use std::{cmp::Ordering, mem::swap, ops::Range};
 
-use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::{iproduct, Itertools};
+use indexmap::IndexMap;
 use nom::{bytes::complete::tag, character::complete::{self, alpha1, one_of}, combinator::{iterator, opt}, multi::separated_list1, sequence::{delimited, pair, terminated, tuple}, IResult, Parser};
 
+use crate::{
+    error::{finish_nom, ParseError},
+    runner::{timed, Solution},
+};
+
 
 #[derive(Debug)]
 struct PartsOrganizer {
-    workflows: HashMap<String, Vec<Flow>>,
+    /// In the order of the input, one workflow per line.
+    workflows: IndexMap<String, Vec<Flow>>,
     parts: Vec<Part>
 }
 
 impl PartsOrganizer {
     fn parse_parts(input: &str) -> IResult<&str, Self> {
         let mut it = iterator(input, terminated(parse_workflow, tag("\n")));
-        let workflows = it.collect::<HashMap<String, Vec<Flow>>>();
+        let workflows = it.collect::<IndexMap<String, Vec<Flow>>>();
         let input = it.finish()?.0;
 
         let (input, _) = tag("\n")(input)?;
@@ -53,18 +58,25 @@ impl PartsOrganizer {
             if name == "A" {
                 score += range.score()
             } else if name != "R" {
-                self.run_flow_ranges(&name, range);
+                stack.extend(self.run_flow_ranges(&name, range));
             }
         }
         score
     }
 
-    fn run_flow_ranges(&self, name: &str, range: XmasRanges) -> impl Iterator<Item=(String, XmasRanges)> {
+    /// Runs `range` through the workflow `name`, yielding every non-empty
+    /// sub range together with the workflow it is sent to.
+    fn run_flow_ranges<'a>(&'a self, name: &str, range: XmasRanges) -> impl Iterator<Item=(String, XmasRanges)> + 'a {
         let flow = self.workflows.get(name).unwrap();
+        let mut remaining = Some(range);
         flow
-            .into_iter();
-        todo!();
-        empty()
+            .iter()
+            .map_while(move |f| {
+                let (matching, rest) = f.split_range(remaining.take()?);
+                remaining = rest;
+                Some(matching.map(|m| (f.send_to.clone(), m)))
+            })
+            .flatten()
     }
 }
 
@@ -95,6 +107,16 @@ impl XmasRanges {
         }
     }
 
+    fn is_empty(&self) -> bool {
+        [&self.x, &self.m, &self.a, &self.s]
+            .into_iter()
+            .any(Range::is_empty)
+    }
+
+    fn non_empty(self) -> Option<Self> {
+        (!self.is_empty()).then_some(self)
+    }
+
     fn score(self) -> u64 {
         (self.x.end - self.x.start)
             * (self.m.end - self.m.start)
@@ -214,8 +236,13 @@ impl Flow {
         }
     }
 
-    fn split_range(&self, range: &XmasRanges) -> Vec<XmasRanges> {
-        self.condition.map_or_else(|| vec![range.clone()], |c| c.split_range(range.clone()))
+    /// Splits `range` into the part sent to `send_to` and the part that
+    /// continues to the next flow.
+    fn split_range(&self, range: XmasRanges) -> (Option<XmasRanges>, Option<XmasRanges>) {
+        match self.condition {
+            Some(condition) => condition.split_range(range),
+            None => (range.non_empty(), None),
+        }
     }
 }
 
@@ -250,9 +277,13 @@ impl Condition {
         part.get_rating(self.rating).cmp(&self.value) == self.ordering
     }
 
-    fn split_range(&self, range: XmasRanges) -> Vec<XmasRanges> {
+    /// Returns the (matching, not matching) parts of `range`, leaving out
+    /// parts that are empty.
+    fn split_range(&self, mut range: XmasRanges) -> (Option<XmasRanges>, Option<XmasRanges>) {
         let r = range.get_range_by_rating(self.rating);
-        let index = self.value + u64::from(self.ordering == Ordering::Greater);
+        let index = (self.value + u64::from(self.ordering == Ordering::Greater)).clamp(r.start, r.end);
+        let matching = range.split_off_range(index, self.rating, self.ordering);
+        (matching.non_empty(), range.non_empty())
     }
 }
 
@@ -272,25 +303,49 @@ fn run_workflow(workflow: &[Flow], part: &Part) -> String {
         .unwrap()
 }
 
-#[aoc_generator(day19)]
-fn parse(input: &str) -> PartsOrganizer {
-    PartsOrganizer::parse_parts(input).unwrap().1
+/// Checks that `in` and every workflow sent to exist and that every workflow
+/// ends in a fallback.
+fn check_workflows(organizer: &PartsOrganizer) -> Result<(), ParseError> {
+    if !organizer.workflows.contains_key("in") {
+        return Err(ParseError::new("in", "no workflow named `in`").on_line(1));
+    }
+    for (line, (name, flows)) in organizer.workflows.iter().enumerate() {
+        let error = |text: &str, reason: String| Err(ParseError::new(text, reason).on_line(line + 1));
+        let known = |to: &str| ["A", "R"].contains(&to) || organizer.workflows.contains_key(to);
+        if let Some(flow) = flows.iter().find(|f| !known(&f.send_to)) {
+            return error(&flow.send_to, format!("no workflow named `{}`", flow.send_to));
+        }
+        if flows.last().is_some_and(|last| last.condition.is_some()) {
+            return error(name, format!("expected workflow `{name}` to end in a fallback without a condition"));
+        }
+    }
+    Ok(())
+}
+
+fn parse(input: &str) -> Result<PartsOrganizer, ParseError> {
+    let organizer = finish_nom(input, PartsOrganizer::parse_parts(input)).map_err(|e| e.in_day(19))?;
+    check_workflows(&organizer).map_err(|e| e.in_day(19))?;
+    Ok(organizer)
 }
 
-#[aoc(day19, part1)]
 fn part1(parts: &PartsOrganizer) -> u64 {
     parts.part1()
 }
 
-#[aoc(day19, part2)]
 fn part2(parts: &PartsOrganizer) -> u64 {
     parts.part2()
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(19, 1, |input| timed(input, parse, part1)),
+    Solution::new(19, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use indoc::indoc;
+    use proptest::{prelude::*, sample::Index};
 
     static INPUT: &str = indoc! {r"
     px{a<2006:qkq,m>2090:A,rfg}
@@ -314,12 +369,98 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(INPUT)), 19114);
+        assert_eq!(part1(&parse(INPUT).unwrap()), 19114);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(INPUT)), 167409079868000);
+        assert_eq!(part2(&parse(INPUT).unwrap()), 167409079868000);
+    }
+
+    #[test]
+    fn part2_condition_at_lower_bound() {
+        let organizer = parse("in{x<1:R,x<2:A,R}\n\n{x=1,m=1,a=1,s=1}").unwrap();
+        assert_eq!(part2(&organizer), 4000 * 4000 * 4000);
+    }
+
+    #[test]
+    fn part2_condition_at_upper_bound() {
+        let organizer = parse("in{m>4000:R,m>3999:A,R}\n\n{x=1,m=1,a=1,s=1}").unwrap();
+        assert_eq!(part2(&organizer), 4000 * 4000 * 4000);
+    }
+
+    #[test]
+    fn part2_everything_accepted() {
+        let organizer = parse("in{a<4001:A,R}\n\n{x=1,m=1,a=1,s=1}").unwrap();
+        assert_eq!(part2(&organizer), 4000u64.pow(4));
+    }
+
+    #[test]
+    fn test_condition_split_range() {
+        let condition = Condition {
+            rating: RatingsEnum::S,
+            ordering: Ordering::Less,
+            value: 1351,
+        };
+        let (matching, rest) = condition.split_range(XmasRanges::new());
+        assert_eq!(matching.unwrap().s, 1..1351);
+        assert_eq!(rest.unwrap().s, 1351..4001);
+
+        let condition = Condition {
+            rating: RatingsEnum::S,
+            ordering: Ordering::Greater,
+            value: 1351,
+        };
+        let (matching, rest) = condition.split_range(XmasRanges::new());
+        assert_eq!(matching.unwrap().s, 1352..4001);
+        assert_eq!(rest.unwrap().s, 1..1352);
+    }
+
+    #[test]
+    fn test_condition_split_range_outside() {
+        let condition = Condition {
+            rating: RatingsEnum::X,
+            ordering: Ordering::Greater,
+            value: 4000,
+        };
+        assert_eq!(condition.split_range(XmasRanges::new()), (None, Some(XmasRanges::new())));
+
+        let condition = Condition {
+            rating: RatingsEnum::X,
+            ordering: Ordering::Less,
+            value: 1,
+        };
+        assert_eq!(condition.split_range(XmasRanges::new()), (None, Some(XmasRanges::new())));
+    }
+
+    #[test]
+    fn test_run_flow_ranges() {
+        let organizer = parse(INPUT).unwrap();
+        let sent = organizer.run_flow_ranges("in", XmasRanges::new()).collect::<Vec<_>>();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].0, "px");
+        assert_eq!(sent[0].1.s, 1..1351);
+        assert_eq!(sent[1].0, "qqz");
+        assert_eq!(sent[1].1.s, 1351..4001);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse("in{x<1:R,A}\n\n{x=1,m=1,a=1}").unwrap_err();
+        assert_eq!((error.day, error.line, error.column), (Some(19), Some(3), Some(13)));
+
+        let error = parse("in{x<1:R,A}\n\n{x=1,m=1,a=1,s=1}\nrest").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(4), Some(1)));
+
+        let error = parse("in{x<1:R,px}\npx{a>5:qs,A}\n\n{x=1,m=1,a=1,s=1}").unwrap_err();
+        assert_eq!((error.day, error.line, error.text.as_str()), (Some(19), Some(2), "qs"));
+        assert_eq!(error.reason, "no workflow named `qs`");
+
+        let error = parse("px{x<1:R,A}\n\n{x=1,m=1,a=1,s=1}").unwrap_err();
+        assert_eq!((error.line, error.reason.as_str()), (Some(1), "no workflow named `in`"));
+
+        let error = parse("in{x<1:R,A}\npx{x<1:R,m>2:A}\n\n{x=1,m=1,a=1,s=1}").unwrap_err();
+        assert_eq!((error.line, error.text.as_str()), (Some(2), "px"));
     }
 
     #[test]
@@ -400,4 +541,80 @@ mod tests {
 
         assert_eq!(parse_ordering(input).unwrap().1, expected)
     }
+
+    const RATINGS: [RatingsEnum; 4] = [RatingsEnum::X, RatingsEnum::M, RatingsEnum::A, RatingsEnum::S];
+
+    fn workflow_name(i: usize) -> String {
+        if i == 0 {
+            "in".to_owned()
+        } else {
+            format!("w{}", (b'a' + i as u8) as char)
+        }
+    }
+
+    /// Random workflows and parts. Workflows only send parts to later
+    /// workflows, so every part ends up in `A` or `R`.
+    fn system() -> impl Strategy<Value = String> {
+        let rule = (prop::sample::select(vec!['x', 'm', 'a', 's']), prop::sample::select(vec!['<', '>']), 1..=4000_u64, any::<Index>());
+        let workflow = (prop::collection::vec(rule, 0..4), any::<Index>());
+        let part = [1..=4000_u64, 1..=4000_u64, 1..=4000_u64, 1..=4000_u64];
+        let workflows = prop::collection::vec(workflow, 1..6);
+        (workflows, prop::collection::vec(part, 1..10)).prop_map(|(workflows, parts)| {
+            let workflows = workflows.iter().enumerate().map(|(i, (rules, fallback))| {
+                let targets: Vec<_> = ["A".to_owned(), "R".to_owned()]
+                    .into_iter()
+                    .chain((i + 1..workflows.len()).map(workflow_name))
+                    .collect();
+                let flows: Vec<_> = rules
+                    .iter()
+                    .map(|(rating, ordering, value, target)| format!("{rating}{ordering}{value}:{}", target.get(&targets)))
+                    .chain([fallback.get(&targets).clone()])
+                    .collect();
+                format!("{}{{{}}}", workflow_name(i), flows.join(","))
+            });
+            let parts = parts.iter().map(|[x, m, a, s]| format!("{{x={x},m={m},a={a},s={s}}}"));
+            format!("{}\n\n{}", workflows.collect::<Vec<_>>().join("\n"), parts.collect::<Vec<_>>().join("\n"))
+        })
+    }
+
+    /// The accepted and rejected ranges of all possible parts.
+    fn outcomes(organizer: &PartsOrganizer) -> (Vec<XmasRanges>, Vec<XmasRanges>) {
+        let (mut accepted, mut rejected) = (Vec::new(), Vec::new());
+        let mut stack = vec![("in".to_owned(), XmasRanges::new())];
+        while let Some((name, range)) = stack.pop() {
+            match name.as_str() {
+                "A" => accepted.push(range),
+                "R" => rejected.push(range),
+                _ => stack.extend(organizer.run_flow_ranges(&name, range)),
+            }
+        }
+        (accepted, rejected)
+    }
+
+    proptest! {
+        #[test]
+        fn ranges_cover_every_part_once(input in system()) {
+            let organizer = parse(&input).unwrap();
+            let (accepted, rejected) = outcomes(&organizer);
+            let accepted_score: u64 = accepted.into_iter().map(XmasRanges::score).sum();
+            let rejected_score: u64 = rejected.into_iter().map(XmasRanges::score).sum();
+            prop_assert_eq!(accepted_score + rejected_score, 4000_u64.pow(4));
+            prop_assert_eq!(part2(&organizer), accepted_score);
+        }
+
+        #[test]
+        fn ranges_agree_with_workflows(input in system()) {
+            let organizer = parse(&input).unwrap();
+            let (accepted, _) = outcomes(&organizer);
+            let in_accepted_range = |part: &&Part| {
+                accepted.iter().any(|range| {
+                    RATINGS
+                        .into_iter()
+                        .all(|rating| range.get_range_by_rating(rating).contains(&part.get_rating(rating)))
+                })
+            };
+            let expected: u64 = organizer.parts.iter().filter(in_accepted_range).map(Part::score).sum();
+            prop_assert_eq!(part1(&organizer), expected);
+        }
+    }
 }
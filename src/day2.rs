@@ -1,90 +1,448 @@
-use aoc_runner_derive::{aoc, aoc_generator};
-use std::{
-    collections::{hash_map::RandomState, HashMap},
-    str::FromStr,
+use std::{collections::BTreeMap, fmt::Display, str::FromStr};
+
+use itertools::Itertools;
+
+use crate::{
+    error::{parse_field, parse_lines, ParseError},
+    format::{self, table, Align, Format},
+    runner::{timed, Solution},
 };
 
-#[derive(Debug, Clone)]
-struct Game {
-    id: usize,
-    games: Vec<HashMap<Color, usize>>,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub id: usize,
+    pub games: Vec<BTreeMap<Color, usize>>,
 }
 
-#[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
-enum Color {
-    Red,
-    Green,
-    Blue,
-}
+/// A cube colour, any single word.
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub struct Color(String);
 
 impl FromStr for Color {
     type Err = String;
 
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.is_empty() && s.chars().all(char::is_alphabetic) {
+            Ok(Self(s.to_owned()))
+        } else {
+            Err(format!("a color is a single word, got: {s}"))
+        }
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The most cubes of each colour a bag holds, colours it doesn't mention it
+/// doesn't hold at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bag(BTreeMap<Color, usize>);
+
+/// The bag of the puzzle, 12 red, 13 green and 14 blue cubes.
+impl Default for Bag {
+    fn default() -> Self {
+        "12 red, 13 green, 14 blue".parse().unwrap()
+    }
+}
+
+/// Parses `<count> <color>, <count> <color>...`, the same format as a draw.
+impl FromStr for Bag {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        cubes(s, s).map(Self)
+    }
+}
+
+impl Display for Bag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cubes = self
+            .0
+            .iter()
+            .map(|(color, count)| format!("{count} {color}"));
+        write!(f, "{}", cubes.format(", "))
+    }
+}
+
+impl Bag {
+    /// Parses one bag per line, skipping blank lines and `#` comments.
+    pub fn parse_list(input: &str) -> Result<Vec<Bag>, ParseError> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|(n, line)| line.parse().map_err(|e: ParseError| e.on_line(n + 1)))
+            .collect()
+    }
+
+    pub fn limit(&self, color: &Color) -> usize {
+        self.0.get(color).copied().unwrap_or(0)
+    }
+
+    /// Product of the cubes of every colour in the bag.
+    pub fn power(&self) -> usize {
+        self.power_of(self.0.keys())
+    }
+
+    /// Product of the cubes of `colors`, zero if the bag lacks one of them.
+    pub fn power_of<'a>(&self, colors: impl IntoIterator<Item = &'a Color>) -> usize {
+        colors.into_iter().map(|color| self.limit(color)).product()
+    }
+
+    pub fn allows(&self, game: &Game) -> bool {
+        game.violations(self).next().is_none()
+    }
+}
+
+/// A draw showing more cubes of a colour than the bag holds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation<'a> {
+    pub game: usize,
+    /// 1 based index of the draw in the game.
+    pub draw: usize,
+    pub color: &'a Color,
+    pub count: usize,
+    pub limit: usize,
+}
+
+impl Display for Violation<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "game {}, draw {}: {} {} but the bag holds {}",
+            self.game, self.draw, self.count, self.color, self.limit
+        )
+    }
+}
+
+/// Cubes of one colour over the draws of a game that show it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorStats {
+    pub min: usize,
+    pub max: usize,
+    pub total: usize,
+    /// Number of draws showing the colour.
+    pub draws: usize,
+}
+
+impl ColorStats {
+    fn new(count: usize) -> Self {
+        Self {
+            min: count,
+            max: count,
+            total: count,
+            draws: 1,
+        }
+    }
+
+    fn add(&mut self, count: usize) {
+        self.min = self.min.min(count);
+        self.max = self.max.max(count);
+        self.total += count;
+        self.draws += 1;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+    pub id: usize,
+    pub draws: usize,
+    pub colors: BTreeMap<Color, ColorStats>,
+}
+
+impl Game {
+    pub fn stats(&self) -> Stats {
+        let mut colors = BTreeMap::<Color, ColorStats>::new();
+        for (color, &count) in self.games.iter().flatten() {
+            match colors.get_mut(color) {
+                Some(stats) => stats.add(count),
+                None => {
+                    colors.insert(color.clone(), ColorStats::new(count));
+                }
+            }
+        }
+        Stats {
+            id: self.id,
+            draws: self.games.len(),
+            colors,
+        }
+    }
+
+    /// The smallest bag every draw of the game is possible with.
+    pub fn minimum_bag(&self) -> Bag {
+        let colors = self.stats().colors.into_iter();
+        Bag(colors.map(|(color, stats)| (color, stats.max)).collect())
+    }
+
+    /// Every draw that isn't possible with `bag`, in order.
+    pub fn violations<'a>(&'a self, bag: &'a Bag) -> impl Iterator<Item = Violation<'a>> {
+        self.games.iter().enumerate().flat_map(move |(n, draw)| {
+            draw.iter()
+                .filter(|&(color, &count)| count > bag.limit(color))
+                .map(move |(color, &count)| Violation {
+                    game: self.id,
+                    draw: n + 1,
+                    color,
+                    count,
+                    limit: bag.limit(color),
+                })
+        })
+    }
+}
+
+/// Per colour `max/min/total` of every game, `-` for colours a game never
+/// shows.
+pub fn stats_table(games: &[Game]) -> String {
+    table(&stats_rows(games), |_| Align::Right)
+}
+
+/// The rows of the [`stats_table`], header first.
+fn stats_rows(games: &[Game]) -> Vec<Vec<String>> {
+    let stats = games.iter().map(Game::stats).collect_vec();
+    let colors = stats
+        .iter()
+        .flat_map(|stats| stats.colors.keys())
+        .sorted()
+        .dedup()
+        .collect_vec();
+    let header = ["game", "draws"]
+        .into_iter()
+        .map(str::to_owned)
+        .chain(colors.iter().map(|color| color.to_string()))
+        .collect();
+    let rows = stats.iter().map(|stats| {
+        let cells = colors.iter().map(|&color| match stats.colors.get(color) {
+            Some(c) => format!("{}/{}/{}", c.max, c.min, c.total),
+            None => "-".to_owned(),
+        });
+        [stats.id.to_string(), stats.draws.to_string()]
+            .into_iter()
+            .chain(cells)
+            .collect()
+    });
+    [header].into_iter().chain(rows).collect()
+}
+
+/// Which of a list of candidate bags allow which games.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feasibility<'a> {
+    pub games: &'a [Game],
+    pub bags: &'a [Bag],
+    /// `possible[game][bag]`, in the order of `games` and `bags`.
+    pub possible: Vec<Vec<bool>>,
+}
+
+impl<'a> Feasibility<'a> {
+    pub fn new(games: &'a [Game], bags: &'a [Bag]) -> Self {
+        let possible = games
+            .iter()
+            .map(|game| bags.iter().map(|bag| bag.allows(game)).collect())
+            .collect();
+        Self {
+            games,
+            bags,
+            possible,
+        }
+    }
+
+    /// A header of the bags, then whether each game is possible with them.
+    pub fn rows(&self) -> Vec<Vec<String>> {
+        let header = ["game".to_owned()]
+            .into_iter()
+            .chain(self.bags.iter().map(Bag::to_string))
+            .collect();
+        let rows = self
+            .games
+            .iter()
+            .zip(&self.possible)
+            .map(|(game, possible)| {
+                [game.id.to_string()]
+                    .into_iter()
+                    .chain(possible.iter().map(bool::to_string))
+                    .collect()
+            });
+        [header].into_iter().chain(rows).collect()
+    }
+
+    /// Sum of the ids of the games each bag allows.
+    pub fn id_sums(&self) -> Vec<usize> {
+        (0..self.bags.len())
+            .map(|bag| {
+                self.games
+                    .iter()
+                    .zip(&self.possible)
+                    .filter(|(_, possible)| possible[bag])
+                    .map(|(game, _)| game.id)
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// The bags, then a game by bag table of `x` for possible and `.` for
+/// impossible, ending with the id sums.
+impl Display for Feasibility<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (n, bag) in self.bags.iter().enumerate() {
+            writeln!(f, "bag {}: {bag}", n + 1)?;
+        }
+        let header = ["game".to_owned()]
+            .into_iter()
+            .chain((1..=self.bags.len()).map(|n| n.to_string()))
+            .collect();
+        let rows = self
+            .games
+            .iter()
+            .zip(&self.possible)
+            .map(|(game, possible)| {
+                let cells = possible
+                    .iter()
+                    .map(|&p| if p { "x" } else { "." }.to_owned());
+                [game.id.to_string()].into_iter().chain(cells).collect()
+            });
+        let sums = ["sum".to_owned()]
+            .into_iter()
+            .chain(self.id_sums().iter().map(usize::to_string))
+            .collect();
+        let rows = [header].into_iter().chain(rows).chain([sums]).collect_vec();
+        write!(f, "{}", table(&rows, |_| Align::Right))
+    }
+}
+
+/// How [`report`] shows the games.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// The games each bag allows and the draws ruling out the others.
+    Violations,
+    /// The [`Feasibility`] table.
+    Matrix,
+    /// The [`stats_table`], ignoring the bags.
+    Stats,
+}
+
+impl FromStr for View {
+    type Err = String;
+
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "red" => Ok(Self::Red),
-            "green" => Ok(Self::Green),
-            "blue" => Ok(Self::Blue),
-            _ => Err(format!("Unknown color received: {s}")),
+            "violations" => Ok(View::Violations),
+            "matrix" => Ok(View::Matrix),
+            "stats" => Ok(View::Stats),
+            _ => Err(format!(
+                "unknown view {s:?}, expected violations, matrix or stats"
+            )),
         }
     }
 }
 
-fn single_color_draw_to_pair(single_color: &str) -> (Color, usize) {
-    let (count, color) = single_color.split_once(' ').unwrap();
-    (color.parse().unwrap(), count.parse().unwrap())
+/// Shows the games of `input` against `bags`, JSON and CSV only hold the
+/// rows of the view.
+pub fn report(input: &str, bags: &[Bag], view: View, format: Format) -> Result<String, ParseError> {
+    let games = parse(input)?;
+    let rows = match (view, format) {
+        (View::Violations, Format::Table) => return Ok(violations(&games, bags)),
+        (View::Violations, _) => violation_rows(&games, bags),
+        (View::Matrix, Format::Table) => return Ok(Feasibility::new(&games, bags).to_string()),
+        (View::Matrix, _) => Feasibility::new(&games, bags).rows(),
+        (View::Stats, _) => stats_rows(&games),
+    };
+    Ok(format::render(&rows, format, |_| Align::Right))
+}
+
+/// Every [`Violation`] of every bag, after a header.
+fn violation_rows(games: &[Game], bags: &[Bag]) -> Vec<Vec<String>> {
+    let header = ["bag", "game", "draw", "color", "count", "limit"].map(str::to_owned);
+    let rows = bags.iter().flat_map(|bag| {
+        games.iter().flat_map(move |game| {
+            game.violations(bag).map(move |violation| {
+                [
+                    bag.to_string(),
+                    violation.game.to_string(),
+                    violation.draw.to_string(),
+                    violation.color.to_string(),
+                    violation.count.to_string(),
+                    violation.limit.to_string(),
+                ]
+            })
+        })
+    });
+    [header].into_iter().chain(rows).map(Vec::from).collect()
+}
+
+/// Which games each of `bags` allows, and why the others aren't possible.
+fn violations(games: &[Game], bags: &[Bag]) -> String {
+    let mut reports = bags.iter().map(|bag| {
+        let (possible, impossible): (Vec<_>, Vec<_>) =
+            games.iter().partition(|game| bag.allows(game));
+        let violations = impossible
+            .iter()
+            .flat_map(|game| game.violations(bag))
+            .map(|violation| format!("\n  {violation}"));
+        format!(
+            "bag {bag}: {} of {} games possible, id sum {}{}",
+            possible.len(),
+            games.len(),
+            possible.iter().map(|game| game.id).sum::<usize>(),
+            violations.format("")
+        )
+    });
+    reports.join("\n\n")
+}
+
+fn single_color_draw_to_pair(line: &str, single_color: &str) -> Result<(Color, usize), ParseError> {
+    let (count, color) = single_color
+        .split_once(' ')
+        .ok_or_else(|| ParseError::at(line, single_color, "expected `<count> <color>`"))?;
+    Ok((parse_field(line, color)?, parse_field(line, count)?))
+}
+
+/// Parses the `<count> <color>, ...` part `s` of `line`.
+fn cubes(line: &str, s: &str) -> Result<BTreeMap<Color, usize>, ParseError> {
+    s.split(", ")
+        .map(|draw| single_color_draw_to_pair(line, draw))
+        .collect()
 }
 
-fn line_to_game(line: &str) -> Game {
-    let (game, draws) = line.split_once(": ").unwrap();
-    let id: usize = game[5..].parse().unwrap();
+fn line_to_game(line: &str) -> Result<Game, ParseError> {
+    let (game, draws) = line
+        .split_once(": ")
+        .ok_or_else(|| ParseError::at(line, line, "expected `Game <id>: <draws>`"))?;
+    let id = game
+        .strip_prefix("Game ")
+        .ok_or_else(|| ParseError::at(line, game, "expected `Game <id>`"))?;
+    let id: usize = parse_field(line, id)?;
     let games = draws
         .split("; ")
-        .map(|game| game.split(", ").map(single_color_draw_to_pair).collect())
-        .collect();
-    Game { id, games }
+        .map(|game| cubes(line, game))
+        .collect::<Result<_, _>>()?;
+    Ok(Game { id, games })
 }
 
-#[aoc_generator(day2)]
-fn parse(input: &str) -> Vec<Game> {
-    input.lines().map(line_to_game).collect()
+fn parse(input: &str) -> Result<Vec<Game>, ParseError> {
+    parse_lines(2, input, line_to_game)
 }
 
-#[aoc(day2, part1)]
 fn part1(input: &[Game]) -> usize {
-    let limits: HashMap<Color, usize, RandomState> =
-        HashMap::from_iter([(Color::Red, 12), (Color::Green, 13), (Color::Blue, 14)]);
+    let bag = Bag::default();
     input
         .iter()
-        .filter(|n| {
-            !n.games
-                .iter()
-                .flatten()
-                .any(|(color, count)| limits[color] < *count)
-        })
+        .filter(|game| bag.allows(game))
         .map(|n| n.id)
         .sum()
 }
 
-#[aoc(day2, part2)]
 fn part2(input: &[Game]) -> usize {
-    let mut input = input.to_vec();
-    input
-        .iter_mut()
-        .map(|n| {
-            n.games.iter_mut().reduce(|acc, draw| {
-                for (color, count) in draw.iter_mut() {
-                    acc.entry(*color)
-                        .and_modify(|n| *n = (*n).max(*count))
-                        .or_insert(*count);
-                }
-                acc
-            })
-        })
-        .map(|game| game.unwrap().values().product::<usize>())
-        .sum()
+    input.iter().map(|game| game.minimum_bag().power()).sum()
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(2, 1, |input| timed(input, parse, part1)),
+    Solution::new(2, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -100,11 +458,120 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(INPUT)), 8);
+        assert_eq!(part1(&parse(INPUT).unwrap()), 8);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(INPUT)), 2286);
+        assert_eq!(part2(&parse(INPUT).unwrap()), 2286);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse("Game 1: 3 blue\nGame 2: 4 b1ue").unwrap_err();
+        assert_eq!(
+            (error.day, error.line, error.column),
+            (Some(2), Some(2), Some(11))
+        );
+        assert_eq!(error.text, "b1ue");
+
+        let error = parse("Game x: 3 blue").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(1), Some(6)));
+
+        let error = parse("3 blue").unwrap_err();
+        assert_eq!(error.line, Some(1));
+
+        let error = Bag::parse_list("# limits\n12 red\n\n13 red, x").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(4), Some(9)));
+    }
+
+    #[test]
+    fn test_open_colors() {
+        let games = parse("Game 7: 2 teal, 1 red; 5 teal\nGame 8: 3 mauve").unwrap();
+        let bag: Bag = "4 teal, 1 red, 9 mauve".parse().unwrap();
+        assert!(!bag.allows(&games[0]));
+        assert!(bag.allows(&games[1]));
+        let violations = games[0].violations(&bag).collect::<Vec<_>>();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].to_string(),
+            "game 7, draw 2: 5 teal but the bag holds 4"
+        );
+        assert_eq!(games[0].minimum_bag().to_string(), "1 red, 5 teal");
+        assert_eq!(games[0].minimum_bag().power(), 5);
+        assert!(!Bag::default().allows(&games[1]));
+    }
+
+    #[test]
+    fn test_power_of() {
+        let bag = parse(INPUT).unwrap()[0].minimum_bag();
+        assert_eq!(bag, "4 red, 2 green, 6 blue".parse().unwrap());
+        let colors = ["red", "blue"].map(|c| c.parse().unwrap());
+        assert_eq!(bag.power_of(&colors), 24);
+        assert_eq!(bag.power_of(&["teal".parse().unwrap()]), 0);
+    }
+
+    #[test]
+    fn test_stats() {
+        let games = parse(INPUT).unwrap();
+        let stats = games[2].stats();
+        assert_eq!(stats.draws, 3);
+        assert_eq!(
+            stats.colors[&"red".parse().unwrap()],
+            ColorStats {
+                min: 1,
+                max: 20,
+                total: 25,
+                draws: 3
+            }
+        );
+        assert_eq!(stats.colors[&"blue".parse().unwrap()].draws, 2);
+        assert_eq!(
+            stats_table(&games[..2]),
+            indoc! {"
+                game  draws   blue  green    red
+                   1      3  6/3/9  2/2/4  4/1/5
+                   2      3  4/1/6  3/1/6  1/1/1"}
+        );
+    }
+
+    #[test]
+    fn test_feasibility() {
+        let games = parse(INPUT).unwrap();
+        let bags = Bag::parse_list("12 red, 13 green, 14 blue\n20 red, 13 green, 15 blue").unwrap();
+        let feasibility = Feasibility::new(&games, &bags);
+        assert_eq!(feasibility.possible[3], vec![false, true]);
+        assert_eq!(feasibility.id_sums(), vec![8, 15]);
+        assert_eq!(
+            feasibility.to_string(),
+            indoc! {"
+                bag 1: 14 blue, 13 green, 12 red
+                bag 2: 15 blue, 13 green, 20 red
+                game  1   2
+                   1  x   x
+                   2  x   x
+                   3  .   x
+                   4  .   x
+                   5  x   x
+                 sum  8  15"}
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let bags = Bag::parse_list("12 red, 13 green, 14 blue\n20 red, 15 blue").unwrap();
+        let text = report(INPUT, &bags, View::Violations, Format::Table).unwrap();
+        assert!(text.starts_with("bag 14 blue, 13 green, 12 red: 3 of 5 games possible, id sum 8"));
+        assert!(text.contains("\n  game 3, draw 1: 20 red but the bag holds 12\n"));
+        assert!(text.contains("bag 15 blue, 20 red: 0 of 5 games possible, id sum 0"));
+
+        let csv = report(INPUT, &bags, View::Violations, Format::Csv).unwrap();
+        assert!(csv.starts_with(
+            "bag,game,draw,color,count,limit\n\"14 blue, 13 green, 12 red\",3,1,red,20,12\n"
+        ));
+        let json = report(INPUT, &bags[..1], View::Matrix, Format::Json).unwrap();
+        assert!(json.contains("\n  {\"game\": 3, \"14 blue, 13 green, 12 red\": false},\n"));
+        let csv = report(INPUT, &bags, View::Stats, Format::Csv).unwrap();
+        assert!(csv.starts_with("game,draws,blue,green,red\n1,3,6/3/9,2/2/4,4/1/5\n"));
     }
 }
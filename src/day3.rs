@@ -1,172 +1,214 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
 
-use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
+use unicode_segmentation::UnicodeSegmentation;
 
-struct Scemantic {
-    numbers: HashMap<Position, Number>,
-    symbols: HashMap<Position, char>,
-    grid_x: usize,
-    grid_y: usize,
-}
+use crate::{
+    error::ParseError,
+    grid::{Coord, Grid},
+    runner::{timed, Solution},
+};
 
-struct Number {
-    value: usize,
-    length: usize,
-}
+/// The void of the puzzle input.
+const VOID: char = '.';
 
-impl Number {
-    fn new(value: usize, length: usize) -> Self {
-        Self { value, length }
-    }
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Cell {
+    Void,
+    Symbol(String),
+    Digit(usize),
 }
 
-#[derive(Debug, Hash, PartialEq, Eq)]
-struct Position {
-    x: usize,
-    y: usize,
+impl Cell {
+    /// Any grapheme that isn't a digit or `void` is a symbol, except for
+    /// blanks and control characters which are more likely formatting
+    /// mistakes.
+    fn parse(grapheme: &str, void: char) -> Result<Self, String> {
+        let mut chars = grapheme.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_digit() => {
+                Ok(Self::Digit(c.to_digit(10).unwrap() as usize))
+            }
+            (Some(c), None) if c == void => Ok(Self::Void),
+            _ if grapheme
+                .chars()
+                .any(|c| c.is_whitespace() || c.is_control()) =>
+            {
+                Err(format!("{grapheme:?}"))
+            }
+            _ => Ok(Self::Symbol(grapheme.to_owned())),
+        }
+    }
 }
 
-impl Position {
-    fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
-    }
+/// A part number and where it is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number {
+    pub value: usize,
+    pub start: Coord,
+    pub length: usize,
 }
 
-impl From<(usize, usize)> for Position {
-    fn from(value: (usize, usize)) -> Self {
-        Self::new(value.0, value.1)
+impl Number {
+    fn cells(&self) -> impl Iterator<Item = Coord> + '_ {
+        (self.start.0..self.start.0 + self.length).map(|x| (x, self.start.1))
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum CharType {
-    Void,
-    Symbol,
-    Number,
+/// Spatial index of the schematic, every cell knows the number covering it
+/// so adjacency questions are answered by looking at neighbouring cells
+/// instead of scanning rectangles.
+pub struct Schematic {
+    cells: Grid<Cell>,
+    /// Index into `numbers` of the number covering each cell.
+    covering: Grid<Option<usize>>,
+    numbers: Vec<Number>,
 }
 
-impl TryFrom<char> for CharType {
-    type Error = String;
-
-    fn try_from(value: char) -> Result<Self, Self::Error> {
-        match value {
-            c if c.is_ascii_digit() => Ok(Self::Number),
-            '.' => Ok(Self::Void),
-            c if c.is_ascii_punctuation() => Ok(Self::Symbol),
-            c => Err(format!("Unknown character received: {c}")),
+impl Schematic {
+    /// Parses a schematic with one cell per extended grapheme cluster, so a
+    /// letter and its combining accents are a single cell, and `void` between
+    /// the numbers and symbols.
+    pub fn parse(input: &str, void: char) -> Result<Self, ParseError> {
+        let mut cells = Vec::with_capacity(input.len());
+        let mut width = None;
+        for (y, line) in input.lines().enumerate() {
+            let error = |part, reason| ParseError::at(line, part, reason).on_line(y + 1).in_day(3);
+            let len = line.graphemes(true).count();
+            let expected = *width.get_or_insert(len);
+            if expected != len {
+                let reason = format!("expected a row of {expected} cells, found {len}");
+                return Err(error(line, reason));
+            }
+            for grapheme in line.graphemes(true) {
+                let cell = Cell::parse(grapheme, void)
+                    .map_err(|e| error(grapheme, format!("unknown cell {e}")))?;
+                cells.push(cell);
+            }
         }
+        Ok(Self::new(Grid::from_cells(width.unwrap_or(0), cells)))
     }
-}
 
-#[aoc_generator(day3)]
-fn parse(input: &str) -> Scemantic {
-    let mut numbers: HashMap<Position, Number> = HashMap::new();
-    let mut symbols: HashMap<Position, char> = HashMap::new();
-    let mut grid_x: usize = 0;
-    let mut grid_y: usize = 0;
-
-    for (y, line) in input.lines().enumerate() {
-        grid_y = y + 1;
-        let mut value = 0;
-        let mut value_length = 0;
-        let mut start_possition = 0;
-        let mut last_value = false;
-        grid_x = line.len();
-        for (x, c) in line.char_indices() {
-            let ctype: CharType = c.try_into().unwrap();
-            if ctype == CharType::Number {
-                if !last_value {
-                    last_value = true;
-                    start_possition = x;
+    fn new(cells: Grid<Cell>) -> Self {
+        let mut covering = Grid::new(cells.width(), cells.height(), None);
+        let mut numbers: Vec<Number> = Vec::new();
+        for ((x, y), cell) in cells.indexed_iter() {
+            let Cell::Digit(digit) = *cell else {
+                continue;
+            };
+            let continued = x > 0 && covering[(x - 1, y)].is_some();
+            match numbers.last_mut() {
+                Some(number) if continued => {
+                    number.value = number.value * 10 + digit;
+                    number.length += 1;
                 }
-                value *= 10;
-                value += c.to_digit(10).unwrap() as usize;
-                value_length += 1;
-            }
-            if last_value && (ctype != CharType::Number || x == grid_x - 1) {
-                numbers.insert(
-                    (start_possition, y).into(),
-                    Number::new(value, value_length),
-                );
-                value = 0;
-                value_length = 0;
-                last_value = false;
-            }
-            if ctype == CharType::Symbol {
-                symbols.insert((x, y).into(), c);
+                _ => numbers.push(Number {
+                    value: digit,
+                    start: (x, y),
+                    length: 1,
+                }),
             }
+            covering[(x, y)] = Some(numbers.len() - 1);
+        }
+        Self {
+            cells,
+            covering,
+            numbers,
         }
     }
-    Scemantic {
-        numbers,
-        symbols,
-        grid_x,
-        grid_y,
+
+    pub fn number_at(&self, coord: Coord) -> Option<&Number> {
+        self.covering
+            .get(coord)
+            .copied()
+            .flatten()
+            .map(|n| &self.numbers[n])
     }
-}
 
-#[aoc(day3, part1)]
-fn part1(scemantic: &Scemantic) -> usize {
-    scemantic
-        .numbers
-        .iter()
-        .filter(|(pos, number)| check_near_symbol(pos, number, scemantic))
-        .map(|(_pos, number)| number.value)
-        .sum()
-}
+    pub fn symbols(&self) -> impl Iterator<Item = (Coord, &str)> {
+        self.cells
+            .indexed_iter()
+            .filter_map(|(coord, cell)| match cell {
+                Cell::Symbol(symbol) => Some((coord, symbol.as_str())),
+                _ => None,
+            })
+    }
+
+    /// The numbers touching `coord`, each once.
+    pub fn numbers_around(&self, coord: Coord) -> Vec<&Number> {
+        self.cells
+            .adjacent(coord)
+            .filter_map(|coord| self.number_at(coord))
+            .unique_by(|number| number.start)
+            .collect()
+    }
 
-fn check_near_symbol(pos: &Position, number: &Number, scemantics: &Scemantic) -> bool {
-    let x = scemantics.grid_x;
-    let y = scemantics.grid_y;
+    /// The symbols touching `number`, each once.
+    pub fn symbols_around(&self, number: &Number) -> Vec<(Coord, &str)> {
+        number
+            .cells()
+            .flat_map(|coord| self.cells.adjacent(coord))
+            .unique()
+            .filter_map(|coord| match &self.cells[coord] {
+                Cell::Symbol(symbol) => Some((coord, symbol.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
 
-    let x_range_start = pos.x.saturating_sub(1);
-    let y_range_start = pos.y.saturating_sub(1);
+    /// Numbers touching at least one symbol.
+    pub fn part_numbers(&self) -> impl Iterator<Item = &Number> {
+        let starts: HashSet<Coord> = self
+            .symbols()
+            .flat_map(|(coord, _)| self.numbers_around(coord))
+            .map(|number| number.start)
+            .collect();
+        self.numbers
+            .iter()
+            .filter(move |number| starts.contains(&number.start))
+    }
 
-    let x_range_end = x.min(pos.x + number.length + 1);
-    let y_range_end = y.min(pos.y + 2);
+    /// Numbers touching no symbol at all.
+    pub fn loose_numbers(&self) -> impl Iterator<Item = &Number> {
+        let parts: HashSet<Coord> = self.part_numbers().map(|number| number.start).collect();
+        self.numbers
+            .iter()
+            .filter(move |number| !parts.contains(&number.start))
+    }
 
-    (x_range_start..x_range_end)
-        .cartesian_product(y_range_start..y_range_end)
-        .any(|pos| scemantics.symbols.contains_key(&pos.into()))
+    /// Every `symbol` touching exactly `k` numbers, with those numbers.
+    pub fn gears<'a>(
+        &'a self,
+        symbol: &'a str,
+        k: usize,
+    ) -> impl Iterator<Item = (Coord, Vec<&'a Number>)> {
+        self.symbols()
+            .filter(move |&(_, c)| c == symbol)
+            .map(|(coord, _)| (coord, self.numbers_around(coord)))
+            .filter(move |(_, numbers)| numbers.len() == k)
+    }
 }
 
-#[aoc(day3, part2)]
-fn part2(scemantic: &Scemantic) -> usize {
-    let mut possible_gears: HashMap<Position, Vec<usize>> = HashMap::new();
-    for (pos, number) in &scemantic.numbers {
-        insert_possible_gears(&mut possible_gears, pos, number, scemantic);
-    }
-    possible_gears
-        .into_values()
-        .filter(|v| v.len() == 2)
-        .map(|n| n.into_iter().product::<usize>())
-        .sum()
+fn parse(input: &str) -> Result<Schematic, ParseError> {
+    Schematic::parse(input, VOID)
 }
 
-fn insert_possible_gears(
-    possible_gears: &mut HashMap<Position, Vec<usize>>,
-    pos: &Position,
-    number: &Number,
-    scemantics: &Scemantic,
-) {
-    let x = scemantics.grid_x;
-    let y = scemantics.grid_y;
-
-    let x_range_start = pos.x.saturating_sub(1);
-    let y_range_start = pos.y.saturating_sub(1);
-
-    let x_range_end = x.min(pos.x + number.length + 1);
-    let y_range_end = y.min(pos.y + 2);
-
-    for pos in (x_range_start..x_range_end).cartesian_product(y_range_start..y_range_end) {
-        let pos: Position = pos.into();
-        if scemantics.symbols.get(&pos) == Some(&'*') {
-            possible_gears.entry(pos).or_default().push(number.value);
-        }
-    }
+fn part1(schematic: &Schematic) -> usize {
+    schematic.part_numbers().map(|number| number.value).sum()
+}
+
+fn part2(schematic: &Schematic) -> usize {
+    schematic
+        .gears("*", 2)
+        .map(|(_, numbers)| numbers.iter().map(|number| number.value).product::<usize>())
+        .sum()
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(3, 1, |input| timed(input, parse, part1)),
+    Solution::new(3, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -188,11 +230,66 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(INPUT)), 4361);
+        assert_eq!(part1(&parse(INPUT).unwrap()), 4361);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(INPUT)), 467835);
+        assert_eq!(part2(&parse(INPUT).unwrap()), 467835);
+    }
+
+    #[test]
+    fn test_index() {
+        let schematic = parse(INPUT).unwrap();
+        assert_eq!(schematic.number_at((1, 0)).map(|n| n.value), Some(467));
+        assert_eq!(schematic.number_at((3, 0)), None);
+        let values = |numbers: Vec<&Number>| numbers.iter().map(|n| n.value).collect_vec();
+        assert_eq!(values(schematic.numbers_around((3, 1))), vec![35, 467]);
+        assert_eq!(values(schematic.numbers_around((3, 4))), vec![617]);
+        let number = schematic.number_at((6, 2)).unwrap();
+        assert_eq!(schematic.symbols_around(number), vec![((6, 3), "#")]);
+        assert_eq!(
+            schematic.loose_numbers().map(|n| n.value).collect_vec(),
+            vec![114, 58]
+        );
+    }
+
+    #[test]
+    fn test_gears() {
+        let schematic = parse(INPUT).unwrap();
+        assert_eq!(schematic.gears("*", 2).count(), 2);
+        assert_eq!(schematic.gears("*", 1).count(), 1);
+        let (coord, numbers) = schematic.gears("$", 1).next().unwrap();
+        assert_eq!((coord, numbers[0].value), ((3, 8), 664));
+        assert_eq!(schematic.gears("#", 2).count(), 0);
+    }
+
+    #[test]
+    fn parse_unknown_character() {
+        let error = parse("467..\n..\t..").err().unwrap();
+        assert_eq!((error.line, error.column), (Some(2), Some(3)));
+        assert_eq!(error.text, "\t");
+        assert_eq!(error.day, Some(3));
+
+        let error = parse("467..\n..").err().unwrap();
+        assert_eq!(error.line, Some(2));
+    }
+
+    #[test]
+    fn test_unicode() {
+        let input = "12★·\n·u\u{308}·9";
+        let schematic = Schematic::parse(input, '·').unwrap();
+        assert_eq!(schematic.number_at((3, 1)).map(|n| n.value), Some(9));
+        let number = schematic.number_at((1, 0)).unwrap();
+        assert_eq!(
+            schematic.symbols_around(number),
+            vec![((1, 1), "u\u{308}"), ((2, 0), "★")]
+        );
+        assert_eq!(part1(&schematic), 21);
+        assert_eq!(schematic.gears("★", 2).count(), 1);
+
+        let schematic = parse(input).unwrap();
+        assert_eq!(schematic.symbols().count(), 5);
+        assert_eq!(part1(&parse("a1\n..").unwrap()), 1);
     }
 }
@@ -1,69 +1,127 @@
-use std::{collections::HashSet, num::ParseIntError, str::FromStr};
+use std::{collections::HashSet, str::FromStr};
 
-use aoc_runner_derive::{aoc, aoc_generator};
+use itertools::Itertools;
 
-struct Scratchcard {
+use crate::{
+    error::{parse_field, parse_lines, ParseError},
+    numbers,
+    runner::{timed, Solution},
+};
+
+pub struct Scratchcard {
     number: usize,
     winning: HashSet<usize>,
     yours: HashSet<usize>,
 }
 
 impl FromStr for Scratchcard {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.strip_prefix("Card ").unwrap();
-        let (card_nr, numbers) = s.split_once(':').unwrap();
-        let (winning, yours) = numbers.split_once('|').unwrap();
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let s = line
+            .strip_prefix("Card ")
+            .ok_or_else(|| ParseError::at(line, line, "expected `Card <number>:`"))?;
+        let (card_nr, numbers) = s
+            .split_once(':')
+            .ok_or_else(|| ParseError::at(line, s, "expected `:` after the card number"))?;
+        let (winning, yours) = numbers
+            .split_once('|')
+            .ok_or_else(|| ParseError::at(line, numbers, "expected `|` between the numbers"))?;
         Ok(Self {
-            number: card_nr.trim().parse::<usize>()? - 1,
-            winning: str_numbers_to_set(winning)?,
-            yours: str_numbers_to_set(yours)?,
+            number: parse_field(line, card_nr)?,
+            winning: numbers::whitespace_separated(line, winning)?,
+            yours: numbers::whitespace_separated(line, yours)?,
         })
     }
 }
 
-fn str_numbers_to_set(s: &str) -> Result<HashSet<usize>, ParseIntError> {
-    s.split(' ')
-        .filter(|n| !n.is_empty())
-        .map(usize::from_str)
-        .collect()
+impl Scratchcard {
+    fn matches(&self) -> usize {
+        self.winning.intersection(&self.yours).count()
+    }
+
+    fn points(&self) -> usize {
+        match self.matches() {
+            0 => 0,
+            n => 2usize.pow(n as u32 - 1),
+        }
+    }
 }
 
-#[aoc_generator(day4)]
-fn parse(input: &str) -> Vec<Scratchcard> {
-    input
-        .lines()
-        .map(Scratchcard::from_str)
-        .map(Result::unwrap)
-        .collect()
+/// What a card ends up with once every card has been scratched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CardTrace {
+    pub number: usize,
+    pub matches: usize,
+    pub points: usize,
+    /// Copies won, not counting the original card.
+    pub copies: usize,
+    /// `(card number, copies)` of the earlier cards the copies came from.
+    pub won_from: Vec<(usize, usize)>,
 }
 
-#[aoc(day4, part1)]
-fn part1(cards: &[Scratchcard]) -> usize {
-    cards
-        .iter()
-        .map(|card| card.winning.intersection(&card.yours).count())
-        .filter(|n| *n != 0)
-        .map(|n| 2usize.pow((n as u32) - 1))
-        .sum()
+impl CardTrace {
+    /// The original card and its copies.
+    pub fn instances(&self) -> usize {
+        self.copies + 1
+    }
 }
 
-#[aoc(day4, part2)]
-fn part2(cards: &[Scratchcard]) -> usize {
-    let mut total_cards = vec![1; cards.len()];
-    for card in cards {
-        let winnings = card.winning.intersection(&card.yours).count();
-        let count_of_current_card = total_cards[card.number];
-        for i in 0..winnings {
-            if let Some(card_number) = total_cards.get_mut(card.number + i + 1) {
-                *card_number += count_of_current_card;
-            }
+/// Plays the cards in order, every instance of a card with `n` matches wins
+/// a copy of each of the `n` cards below it, whatever their numbers.
+pub fn cascade(cards: &[Scratchcard]) -> Vec<CardTrace> {
+    let mut traces = cards
+        .iter()
+        .map(|card| CardTrace {
+            number: card.number,
+            matches: card.matches(),
+            points: card.points(),
+            copies: 0,
+            won_from: Vec::new(),
+        })
+        .collect_vec();
+    for n in 0..traces.len() {
+        let (number, matches, instances) =
+            (traces[n].number, traces[n].matches, traces[n].instances());
+        for below in traces.iter_mut().skip(n + 1).take(matches) {
+            below.copies += instances;
+            below.won_from.push((number, instances));
         }
     }
-    total_cards.iter().sum()
+    traces
+}
+
+/// The [`cascade`] of the cards in `input`.
+pub fn traces(input: &str) -> Result<Vec<CardTrace>, ParseError> {
+    Ok(cascade(&parse(input)?))
+}
+
+fn parse(input: &str) -> Result<Vec<Scratchcard>, ParseError> {
+    let mut seen = HashSet::new();
+    parse_lines(4, input, |line| {
+        let card = Scratchcard::from_str(line)?;
+        if seen.insert(card.number) {
+            Ok(card)
+        } else {
+            let reason = format!("card {} appears twice", card.number);
+            Err(ParseError::at(line, line, reason))
+        }
+    })
+}
+
+fn part1(cards: &[Scratchcard]) -> usize {
+    cards.iter().map(Scratchcard::points).sum()
+}
+
+fn part2(cards: &[Scratchcard]) -> usize {
+    cascade(cards).iter().map(CardTrace::instances).sum()
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(4, 1, |input| timed(input, parse, part1)),
+    Solution::new(4, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,11 +139,59 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(INPUT)), 13);
+        assert_eq!(part1(&parse(INPUT).unwrap()), 13);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(INPUT)), 30);
+        assert_eq!(part2(&parse(INPUT).unwrap()), 30);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse("Card 1: 41 48 | 83 4x").err().unwrap();
+        assert_eq!((error.line, error.column), (Some(1), Some(20)));
+        assert_eq!(error.text, "4x");
+
+        let error = parse("Card 1: 41 48 83 86").err().unwrap();
+        assert_eq!(error.column, Some(8));
+
+        let error = parse("Card 2: 1 | 1\nCard 2: 3 | 4").err().unwrap();
+        assert_eq!(error.line, Some(2));
+    }
+
+    #[test]
+    fn test_cascade() {
+        let traces = traces(INPUT).unwrap();
+        assert_eq!(
+            traces[3],
+            CardTrace {
+                number: 4,
+                matches: 1,
+                points: 1,
+                copies: 7,
+                won_from: vec![(1, 1), (2, 2), (3, 4)],
+            }
+        );
+        assert_eq!(
+            traces.iter().map(CardTrace::instances).collect_vec(),
+            vec![1, 2, 4, 8, 14, 1]
+        );
+    }
+
+    #[test]
+    fn test_card_numbers() {
+        let input = indoc! {"
+            Card 10: 1 2 | 1 2
+            Card 3: 5 | 5
+            Card 0: 7 | 8
+            Card 11: 1 | 1
+        "};
+        let traces = cascade(&parse(input).unwrap());
+        assert_eq!(traces[1].won_from, vec![(10, 1)]);
+        assert_eq!(traces[2].won_from, vec![(10, 1), (3, 2)]);
+        assert_eq!(traces[3].copies, 0);
+        assert_eq!(part2(&parse(input).unwrap()), 1 + 2 + 4 + 1);
+        assert_eq!(part1(&parse(input).unwrap()), 4);
     }
 }
@@ -1,183 +1,419 @@
-use std::{iter::FromIterator, num::ParseIntError, ops::Range, str::FromStr};
+use std::{collections::HashMap, ops::Range, str::FromStr};
 
-use aoc_runner_derive::{aoc, aoc_generator};
+use itertools::Itertools;
 
-struct Almanac {
+use crate::{
+    error::{lines_before, ParseError},
+    numbers,
+    runner::{timed, Solution},
+};
+
+pub struct Almanac {
     seeds: Vec<usize>,
     seeds_ranges: Vec<Range<usize>>,
     mappings: Vec<Mapping>,
+    /// The index of the map from every source category, the edges of the
+    /// category graph [`Almanac::resolve`] walks.
+    graph: HashMap<String, usize>,
+    /// Suspicious but valid parts of the input, see [`Almanac::validation`].
+    warnings: Vec<ParseError>,
 }
 
-struct Mapping {
-    _source: String,
-    _destination: String,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    source: String,
+    destination: String,
     mappings: Vec<Map>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct Map {
     dest_range: Range<usize>,
     source_range: Range<usize>,
 }
 
 impl FromStr for Almanac {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         let mut str_maps = input.split("\n\n");
-        let seeds: Vec<_> =
-            str_numbers_to_collecatble(str_maps.next().unwrap().split_once(": ").unwrap().1)?;
+        let seeds_line = str_maps.next().unwrap_or_default();
+        let (_, seeds_str) = numbers::split_label(seeds_line).map_err(|e| e.on_line(1))?;
+        let seeds: Vec<usize> =
+            numbers::whitespace_separated(seeds_line, seeds_str).map_err(|e| e.on_line(1))?;
+        if !seeds.len().is_multiple_of(2) {
+            return Err(ParseError::in_input(
+                input,
+                seeds_str,
+                "expected pairs of seed ranges",
+            ));
+        }
 
-        let seeds_ranges = seeds.chunks(2).map(|c| c[0]..(c[0] + c[1])).collect();
+        let seeds_ranges: Vec<_> = seeds
+            .chunks(2)
+            .map(|c| Some(c[0]..c[0].checked_add(c[1])?))
+            .collect::<Option<_>>()
+            .ok_or_else(|| ParseError::in_input(input, seeds_str.trim(), "seed range overflows"))?;
+        let mut warnings = seeds_ranges
+            .iter()
+            .sorted_by_key(|range| range.start)
+            .tuple_windows()
+            .filter(|(a, b)| b.start < a.end)
+            .map(|(a, b)| {
+                let reason = format!("seed ranges {a:?} and {b:?} overlap");
+                ParseError::in_input(input, seeds_str.trim(), reason)
+            })
+            .collect_vec();
 
-        let mappings = str_maps
-            .map(Mapping::from_str)
+        let blocks = str_maps.collect_vec();
+        let mappings = blocks
+            .iter()
+            .map(|map| Mapping::from_str(map).map_err(|e| e.offset_lines(lines_before(input, map))))
             .collect::<Result<Vec<_>, _>>()?;
+        let graph = category_graph(input, &blocks, &mappings)?;
+        for block in blocks {
+            let lines = lines_before(input, block);
+            warnings.extend(destination_overlaps(block).map(|e| e.offset_lines(lines)));
+        }
 
         Ok(Self {
             seeds,
             seeds_ranges,
             mappings,
+            graph,
+            warnings,
         })
     }
 }
 
+/// Links every source category to its map and checks that the graph is a
+/// single path from `seed` to `location`, with the maps in path order.
+fn category_graph(
+    input: &str,
+    blocks: &[&str],
+    mappings: &[Mapping],
+) -> Result<HashMap<String, usize>, ParseError> {
+    let header = |n: usize| blocks[n].lines().next().unwrap_or_default();
+    let mut graph = HashMap::new();
+    let mut second = None;
+    for (n, mapping) in mappings.iter().enumerate() {
+        if graph.contains_key(&mapping.source) {
+            second = second.or(Some(n));
+        } else {
+            graph.insert(mapping.source.clone(), n);
+        }
+    }
+
+    let mut category = "seed";
+    let mut n = 0;
+    let path = loop {
+        let reason = match graph.get(category) {
+            _ if category == "location" && n == mappings.len() => break Ok(()),
+            _ if category == "location" => {
+                "the map isn't on the path from `seed` to `location`".to_owned()
+            }
+            Some(&next) if next == n => {
+                category = &mappings[n].destination;
+                n += 1;
+                continue;
+            }
+            Some(&next) if next > n => {
+                format!("maps out of order, the map from `{category}` has to come before this one")
+            }
+            Some(_) => {
+                n -= 1;
+                format!("the map leads back to `{category}`")
+            }
+            None if n < mappings.len() => format!("expected a map from `{category}`"),
+            None => {
+                n = mappings.len().saturating_sub(1);
+                format!("no map from `{category}` on to `location`")
+            }
+        };
+        break Err((n, reason));
+    };
+    let (n, reason) = match (path, second) {
+        (Err((n, reason)), second) if second.is_none_or(|second| second > n) => (n, reason),
+        (_, Some(second)) => (
+            second,
+            format!("second map from `{}`", mappings[second].source),
+        ),
+        (_, None) => return Ok(graph),
+    };
+    let text = if blocks.is_empty() { input } else { header(n) };
+    Err(ParseError::in_input(input, text, reason))
+}
+
+/// Maps of `block` whose destination range overlaps another, which leaves
+/// some numbers of the next category with several sources.
+fn destination_overlaps(block: &str) -> impl Iterator<Item = ParseError> + '_ {
+    block
+        .lines()
+        .skip(1)
+        .filter_map(|line| Some((line, Map::from_str(line).ok()?)))
+        .sorted_by_key(|(_, map)| map.dest_range.start)
+        .tuple_windows()
+        .filter(|((_, a), (_, b))| b.dest_range.start < a.dest_range.end)
+        .map(|((a, _), (b, _))| {
+            let (earlier, later) = in_order(a, b);
+            let reason = format!("destination range overlaps the one of `{earlier}`");
+            ParseError::in_input(block, later, reason)
+        })
+}
+
+/// `a` and `b`, two slices of the same input, in input order.
+fn in_order<'a>(a: &'a str, b: &'a str) -> (&'a str, &'a str) {
+    if a.as_ptr() < b.as_ptr() {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
 impl FromStr for Mapping {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut lines = s.lines();
-        let header = lines.next().unwrap();
+        let header = lines.next().unwrap_or_default();
         let (source, destination) = header
             .strip_suffix(" map:")
-            .unwrap()
-            .split_once("-to-")
-            .unwrap();
-        let mut mappings = lines.map(Map::from_str).collect::<Result<Vec<_>, _>>()?;
-        mappings.sort_by_key(|map| map.source_range.start);
+            .and_then(|header| header.split_once("-to-"))
+            .ok_or_else(|| {
+                ParseError::in_input(s, header, "expected `<source>-to-<destination> map:`")
+            })?;
+        let mut maps = lines
+            .enumerate()
+            .map(|(n, line)| {
+                Map::from_str(line)
+                    .map(|map| (line, map))
+                    .map_err(|e| e.on_line(n + 2))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        maps.sort_by_key(|(_, map)| map.source_range.start);
+        for ((a, a_map), (b, b_map)) in maps.iter().tuple_windows() {
+            if b_map.source_range.start < a_map.source_range.end {
+                let (earlier, later) = in_order(a, b);
+                let reason = format!("source range overlaps the one of `{earlier}`");
+                return Err(ParseError::in_input(s, later, reason));
+            }
+        }
         Ok(Self {
-            _source: source.to_string(),
-            _destination: destination.to_string(),
-            mappings,
+            source: source.to_string(),
+            destination: destination.to_string(),
+            mappings: maps.into_iter().map(|(_, map)| map).collect(),
         })
     }
 }
 
 impl FromStr for Map {
-    type Err = ParseIntError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let values: Vec<_> = str_numbers_to_collecatble(s)?;
-        assert_eq!(values.len(), 3);
+        let values: Vec<usize> = numbers::whitespace_separated(s, s)?;
+        if values.len() != 3 {
+            return Err(ParseError::at(
+                s,
+                s,
+                format!("expected 3 numbers, found {}", values.len()),
+            ));
+        }
+        let range = |start: usize| {
+            let end = start
+                .checked_add(values[2])
+                .ok_or_else(|| ParseError::at(s, s, "range overflows"))?;
+            Ok(start..end)
+        };
         Ok(Self {
-            dest_range: values[0]..(values[0] + values[2]),
-            source_range: values[1]..(values[1] + values[2]),
+            dest_range: range(values[0])?,
+            source_range: range(values[1])?,
         })
     }
 }
 
 impl Almanac {
-    fn map_all_seeds(&self) -> Vec<usize> {
-        self.seeds
-            .iter()
-            .copied()
-            .map(|mut n| {
-                for map in &self.mappings {
-                    n = map.map_source_to_dest(n);
-                }
-                n
-            })
-            .collect()
+    /// The maps on the way from category `from` to category `to` in the
+    /// category graph folded into a single map, `None` if `to` can't be
+    /// reached from `from`.
+    pub fn resolve(&self, from: &str, to: &str) -> Option<Mapping> {
+        let mut chain = Mapping::identity(from, from);
+        while chain.destination != to {
+            let &next = self.graph.get(&chain.destination)?;
+            chain = chain.then(&self.mappings[next]);
+        }
+        Some(chain)
     }
-    fn min_of_all_seeds_ranges(&self) -> usize {
-        self.seeds_ranges
-            .iter()
-            .cloned()
-            .map(|seed_range| {
-                let mut current_seed_ranges = vec![seed_range];
-                for map in &self.mappings {
-                    let mut next_ranges = Vec::new();
-                    for seed_range in current_seed_ranges {
-                        next_ranges.extend(map.map_source_range_to_dest_ranges(seed_range));
-                    }
-                    current_seed_ranges = next_ranges;
-                }
-                current_seed_ranges
-                    .into_iter()
-                    .map(|seed_range| seed_range.start)
-                    .min()
-                    .unwrap()
-            })
-            .min()
-            .unwrap()
+
+    /// One line per warning, or a note that there are none.
+    pub fn validation(&self) -> String {
+        if self.warnings.is_empty() {
+            "no issues found".to_owned()
+        } else {
+            self.warnings.iter().join("\n")
+        }
+    }
+
+    /// The whole seed to location chain folded into a single map.
+    pub fn locations(&self) -> Mapping {
+        self.resolve("seed", "location")
+            .expect("the almanac is checked to lead from seeds to locations")
     }
 }
 
 impl Mapping {
-    fn map_source_to_dest(&self, source: usize) -> usize {
+    fn identity(source: &str, destination: &str) -> Self {
+        Self {
+            source: source.to_owned(),
+            destination: destination.to_owned(),
+            mappings: Vec::new(),
+        }
+    }
+
+    pub fn map_source_to_dest(&self, source: usize) -> usize {
         self.mappings
             .iter()
             .find(|map| (map.source_range.start..(map.source_range.end)).contains(&source))
-            .map_or(source, |map| source - map.source_range.start + map.dest_range.start)
+            .map_or(source, |map| {
+                source - map.source_range.start + map.dest_range.start
+            })
     }
 
-    fn map_source_range_to_dest_ranges(&self, mut range: Range<usize>) -> Vec<Range<usize>> {
-        let mut ranges = Vec::new();
+    /// `(source range, destination start)` pieces covering every source,
+    /// the unmapped gaps between the maps included.
+    ///
+    /// Where maps overlap the one starting first wins, like in
+    /// [`Mapping::map_source_to_dest`].
+    fn pieces(&self) -> Vec<(Range<usize>, usize)> {
+        let mut pieces = Vec::new();
+        let mut start = 0;
         for map in &self.mappings {
-            if range.end < map.source_range.start {
-                // remaning range ends before current range and as this is the
-                // lowest source range avaliblle we add the remaing range to
-                // ranges and break the loop
-                break;
-            } else if range.start >= map.source_range.end {
-                // do nothing as the remaining range is larger than the mapping range
+            let from = map.source_range.start.max(start);
+            if from >= map.source_range.end {
                 continue;
             }
-            if range.start < map.source_range.start {
-                ranges.push(range.start..map.source_range.start);
-                range = map.source_range.start..range.end;
+            if start < from {
+                pieces.push((start..from, start));
             }
-            let mapped_range_stop = range.end.min(map.source_range.end);
-            let mapped_len = mapped_range_stop - range.start;
-            let offset = range.start - map.source_range.start;
-            ranges.push(
-                (offset + map.dest_range.start)..(map.dest_range.start + offset + mapped_len),
-            );
-            if range.end <= map.source_range.end {
-                return ranges;
+            let dest = map.dest_range.start + (from - map.source_range.start);
+            pieces.push((from..map.source_range.end, dest));
+            start = map.source_range.end;
+        }
+        if start < usize::MAX {
+            pieces.push((start..usize::MAX, start));
+        }
+        pieces
+    }
+
+    /// Builds a normalised mapping from sorted `pieces`, leaving out the
+    /// pieces mapping to themselves and merging the ones that continue each
+    /// other.
+    fn from_pieces(source: &str, destination: &str, pieces: Vec<(Range<usize>, usize)>) -> Self {
+        let mappings = pieces
+            .into_iter()
+            .filter(|(range, dest)| range.start != *dest)
+            .coalesce(|(a, a_dest), (b, b_dest)| {
+                if a.end == b.start && a_dest + a.len() == b_dest {
+                    Ok((a.start..b.end, a_dest))
+                } else {
+                    Err(((a, a_dest), (b, b_dest)))
+                }
+            })
+            .map(|(range, dest)| Map {
+                dest_range: dest..dest + range.len(),
+                source_range: range,
+            })
+            .collect();
+        Self {
+            source: source.to_owned(),
+            destination: destination.to_owned(),
+            mappings,
+        }
+    }
+
+    /// The mapping applying `self` and then `next`.
+    pub fn then(&self, next: &Mapping) -> Mapping {
+        debug_assert_eq!(self.destination, next.source, "the mappings don't connect");
+        let second = next.pieces();
+        let mut pieces = Vec::new();
+        for (source, dest) in self.pieces() {
+            let image = dest..dest + source.len();
+            let first = second.partition_point(|(range, _)| range.end <= image.start);
+            for (range, next_dest) in second[first..]
+                .iter()
+                .take_while(|(range, _)| range.start < image.end)
+            {
+                let start = image.start.max(range.start);
+                let end = image.end.min(range.end);
+                let from = source.start + (start - image.start);
+                pieces.push((
+                    from..from + (end - start),
+                    next_dest + (start - range.start),
+                ));
             }
-            range = map.source_range.end..range.end;
         }
-        ranges.push(range);
-        ranges
+        Self::from_pieces(&self.source, &next.destination, pieces)
     }
-}
 
-fn str_numbers_to_collecatble<T>(s: &str) -> Result<T, ParseIntError>
-where
-    T: FromIterator<usize>,
-{
-    s.split(' ')
-        .filter(|n| !n.is_empty())
-        .map(usize::from_str)
-        .collect()
+    /// The sources mapped into `range`, the inverse of the mapping.
+    pub fn preimage(&self, range: Range<usize>) -> Vec<Range<usize>> {
+        self.pieces()
+            .into_iter()
+            .filter_map(|(source, dest)| {
+                let start = range.start.max(dest);
+                let end = range.end.min(dest + source.len());
+                (start < end).then(|| source.start + (start - dest)..source.start + (end - dest))
+            })
+            .sorted_by_key(|range| range.start)
+            .coalesce(|a, b| {
+                if a.end == b.start {
+                    Ok(a.start..b.end)
+                } else {
+                    Err((a, b))
+                }
+            })
+            .collect()
+    }
+
+    /// The lowest destination of the sources in `range`, only looking at the
+    /// start of every piece.
+    fn min_of_range(&self, range: Range<usize>) -> Option<usize> {
+        self.pieces()
+            .into_iter()
+            .filter(|(source, _)| source.start < range.end && range.start < source.end)
+            .map(|(source, dest)| dest + (range.start.max(source.start) - source.start))
+            .min()
+    }
 }
 
-#[aoc_generator(day5)]
-fn parse(input: &str) -> Almanac {
-    input.parse().unwrap()
+fn parse(input: &str) -> Result<Almanac, ParseError> {
+    input.parse().map_err(|e: ParseError| e.in_day(5))
 }
 
-#[aoc(day5, part1)]
 fn part1(input: &Almanac) -> usize {
-    input.map_all_seeds().into_iter().min().unwrap()
+    let locations = input.locations();
+    input
+        .seeds
+        .iter()
+        .map(|&seed| locations.map_source_to_dest(seed))
+        .min()
+        .unwrap()
 }
 
-#[aoc(day5, part2)]
 fn part2(input: &Almanac) -> usize {
-    input.min_of_all_seeds_ranges()
+    let locations = input.locations();
+    input
+        .seeds_ranges
+        .iter()
+        .filter_map(|range| locations.min_of_range(range.clone()))
+        .min()
+        .unwrap()
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(5, 1, |input| timed(input, parse, part1)),
+    Solution::new(5, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -222,28 +458,224 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(INPUT)), 35);
+        assert_eq!(part1(&parse(INPUT).unwrap()), 35);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(INPUT)), 46)
+        assert_eq!(part2(&parse(INPUT).unwrap()), 46)
     }
 
     #[test]
+    #[allow(clippy::single_range_in_vec_init)]
     fn part2_best_case_one() {
-        let mut input = parse(INPUT);
+        let mut input = parse(INPUT).unwrap();
         input.seeds_ranges = vec![(82..83)];
         assert_eq!(part2(&input), 46);
     }
 
     #[test]
+    #[allow(clippy::single_range_in_vec_init)]
     fn part2_best_case_range() {
-        let mut input = parse(INPUT);
+        let mut input = parse(INPUT).unwrap();
         input.seeds_ranges = vec![(81..83)];
         assert_eq!(part2(&input), 46);
     }
 
+    #[test]
+    fn test_compose() {
+        let almanac = parse(INPUT).unwrap();
+        let locations = almanac.locations();
+        for seed in 0..120 {
+            let stepwise = almanac
+                .mappings
+                .iter()
+                .fold(seed, |n, mapping| mapping.map_source_to_dest(n));
+            assert_eq!(locations.map_source_to_dest(seed), stepwise, "seed {seed}");
+        }
+        let adjacent = locations.mappings.iter().tuple_windows().any(|(a, b)| {
+            a.source_range.end == b.source_range.start && a.dest_range.end == b.dest_range.start
+        });
+        assert!(!adjacent, "adjacent pieces should have been merged");
+        assert!(locations
+            .mappings
+            .iter()
+            .all(|map| map.source_range.start != map.dest_range.start));
+    }
+
+    #[test]
+    fn test_merge() {
+        let split: Mapping = "a-to-b map:\n10 0 5\n15 5 5".parse().unwrap();
+        assert_eq!(split.mappings.len(), 2);
+        let merged = split.then(&Mapping::identity("b", "b"));
+        assert_eq!(
+            merged.mappings,
+            vec![Map {
+                dest_range: 10..20,
+                source_range: 0..10
+            }]
+        );
+
+        let first: Mapping = "a-to-b map:\n10 0 5".parse().unwrap();
+        let second: Mapping = "b-to-c map:\n15 10 5\n5 15 5".parse().unwrap();
+        let composed = first.then(&second);
+        let sources = composed.mappings.iter().map(|map| map.source_range.clone());
+        assert_eq!(sources.collect_vec(), vec![0..5, 10..15, 15..20]);
+        assert_eq!(composed.map_source_to_dest(3), 18);
+
+        let back: Mapping = "b-to-a map:\n0 10 5".parse().unwrap();
+        let round_trip = first.then(&back);
+        assert_eq!(
+            (round_trip.source.as_str(), round_trip.destination.as_str()),
+            ("a", "a")
+        );
+        assert_eq!(round_trip.map_source_to_dest(3), 3);
+        assert_eq!(
+            round_trip.mappings,
+            vec![Map {
+                dest_range: 0..5,
+                source_range: 10..15
+            }]
+        );
+    }
+
+    #[test]
+    fn test_preimage() {
+        let locations = parse(INPUT).unwrap().locations();
+        let seeds = locations.preimage(46..47);
+        assert!(seeds.iter().any(|range| range.contains(&82)));
+        for range in &seeds {
+            assert_eq!(locations.map_source_to_dest(range.start), 46);
+        }
+        let seed_to_soil = &parse(INPUT).unwrap().mappings[0];
+        assert_eq!(seed_to_soil.preimage(50..52), vec![98..100]);
+        assert_eq!(seed_to_soil.preimage(10..12), vec![10..12]);
+        assert_eq!(seed_to_soil.preimage(98..100), vec![96..98]);
+    }
+
+    #[test]
+    fn test_validation() {
+        let almanac = parse(INPUT).unwrap();
+        assert_eq!(almanac.validation(), "no issues found");
+        assert!(almanac.locations().preimage(46..47).contains(&(82..83)));
+    }
+
+    #[test]
+    fn overflow_and_overlaps() {
+        let big = format!(
+            "seeds: 1 2\n\nseed-to-location map:\n{} 1 2",
+            usize::MAX - 1
+        );
+        let error = parse(&big).err().unwrap();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (Some(4), "range overflows")
+        );
+
+        let error = parse(&format!("seeds: {} 2", usize::MAX)).err().unwrap();
+        assert_eq!(error.reason, "seed range overflows");
+
+        let overlapping = "seeds: 1 2\n\nseed-to-location map:\n0 10 5\n20 0 5\n30 12 3";
+        let error = parse(overlapping).err().unwrap();
+        assert_eq!((error.line, error.column), (Some(6), Some(1)));
+        assert_eq!(error.reason, "source range overlaps the one of `0 10 5`");
+
+        let almanac = parse("seeds: 1 5 3 2\n\nseed-to-location map:\n0 10 5\n3 20 5").unwrap();
+        let validation = almanac.validation();
+        assert_eq!(
+            validation,
+            "line 1, column 8, seed ranges 1..6 and 3..5 overlap: \"1 5 3 2\"\n\
+             line 5, column 1, destination range overlaps the one of `0 10 5`: \"3 20 5\""
+        );
+        assert_eq!(parse(INPUT).unwrap().validation(), "no issues found");
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse("seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n52 x 48")
+            .err()
+            .unwrap();
+        assert_eq!(
+            (error.day, error.line, error.column),
+            (Some(5), Some(5), Some(4))
+        );
+        assert_eq!(error.text, "x");
+
+        let error = parse("seeds: 79 14\n\nseed-to-soil map:\n50 98")
+            .err()
+            .unwrap();
+        assert_eq!(error.line, Some(4));
+
+        let error = parse("seeds: 79 14\n\nseed-to-soil:\n50 98 2")
+            .err()
+            .unwrap();
+        assert_eq!(error.line, Some(3));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let almanac = parse(INPUT).unwrap();
+        let soil_to_humidity = almanac.resolve("soil", "humidity").unwrap();
+        assert_eq!(
+            (
+                soil_to_humidity.source.as_str(),
+                soil_to_humidity.destination.as_str()
+            ),
+            ("soil", "humidity")
+        );
+        let stepwise = almanac.mappings[1..6]
+            .iter()
+            .fold(81, |n, mapping| mapping.map_source_to_dest(n));
+        assert_eq!(soil_to_humidity.map_source_to_dest(81), stepwise);
+        assert_eq!(almanac.resolve("water", "water").unwrap().mappings, vec![]);
+        assert_eq!(
+            almanac.resolve("location", "location").unwrap().mappings,
+            vec![]
+        );
+        assert!(almanac.resolve("humidity", "soil").is_none());
+        assert!(almanac.resolve("seed", "color").is_none());
+    }
+
+    #[test]
+    fn chain_errors() {
+        let swapped = INPUT.replacen("seed-to-soil", "soil-to-seed", 1);
+        let error = parse(&swapped).err().unwrap();
+        assert_eq!(
+            (error.line, error.text.as_str()),
+            (Some(3), "soil-to-seed map:")
+        );
+
+        let blocks = INPUT.split("\n\n").collect_vec();
+        let out_of_order = [blocks[0], blocks[2], blocks[1]].join("\n\n");
+        let error = parse(&out_of_order).err().unwrap();
+        assert_eq!(error.line, Some(3));
+        assert!(error.reason.contains("out of order"), "{error}");
+
+        let missing = blocks[..blocks.len() - 1].join("\n\n");
+        let error = parse(&missing).err().unwrap();
+        assert_eq!(error.text, "temperature-to-humidity map:");
+        assert!(error.reason.contains("`humidity`"), "{error}");
+
+        let twice = [blocks[0], blocks[1], blocks[1]].join("\n\n");
+        let error = parse(&twice).err().unwrap();
+        assert!(
+            error.reason.starts_with("second map from `seed`"),
+            "{error}"
+        );
+
+        let back = [blocks[0], blocks[1], "soil-to-seed map:\n0 0 1"].join("\n\n");
+        let error = parse(&back).err().unwrap();
+        assert_eq!(
+            (error.text.as_str(), error.reason.as_str()),
+            ("soil-to-seed map:", "the map leads back to `seed`")
+        );
+
+        let stray = format!("{INPUT}\nlocation-to-seed map:\n0 0 1");
+        let error = parse(&stray).err().unwrap();
+        assert_eq!(error.text, "location-to-seed map:");
+        assert!(error.reason.contains("isn't on the path"), "{error}");
+    }
+
     // #[test]
     // fn part2_by_part1() {
     //     let mut input = parse(include_str!("../input/2023/day5.txt"));
@@ -1,90 +1,273 @@
-use std::{num::ParseIntError, str::FromStr};
+use std::ops::{Range, RangeInclusive};
 
-use aoc_runner_derive::{aoc, aoc_generator};
+use crate::{
+    error::{parse_field, ParseError},
+    numbers,
+    runner::{timed, Solution},
+};
 
-#[derive(Debug)]
-struct Race {
+/// A race of `time` ms with a record of `distance` mm.
+///
+/// Holding the button for `hold` ms makes the boat go
+/// `min(acceleration * hold + boosts, max_speed)` mm/ms once it starts
+/// moving, `start_delay` ms after the button is released.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Race {
     time: usize,
     distance: usize,
+    acceleration: usize,
+    max_speed: Option<usize>,
+    start_delay: usize,
+    boosts: Vec<Boost>,
+}
+
+/// Extra speed gained per ms during the first `duration` ms of holding the
+/// button, on top of the acceleration and any other boost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Boost {
+    pub extra: usize,
+    pub duration: usize,
 }
 
 impl From<(usize, usize)> for Race {
     fn from(value: (usize, usize)) -> Self {
+        Self::new(value.0, value.1)
+    }
+}
+
+fn parse_line_numbers(input: &str) -> Result<Vec<usize>, ParseError> {
+    numbers::labelled(input).map(|(_, numbers)| numbers)
+}
+
+fn parse_line_bad_kerning(input: &str) -> Result<usize, ParseError> {
+    numbers::bad_kerning(input, numbers::split_label(input)?.1)
+}
+
+fn split_lines(input: &str) -> Result<(&str, &str), ParseError> {
+    let mut lines = input.lines();
+    lines
+        .next()
+        .zip(lines.next())
+        .ok_or_else(|| ParseError::new(input, "expected a time and a distance line").in_day(6))
+}
+
+/// Applies the optional `Acceleration:`, `Max speed:`, `Start delay:` and
+/// `Boost: <extra> for <duration>` lines after the distances to `race`.
+fn with_physics(input: &str, mut race: Race) -> Result<Race, ParseError> {
+    for (n, line) in input.lines().enumerate().skip(2) {
+        let setting = || -> Result<Race, ParseError> {
+            let (label, value) = numbers::split_label(line)?;
+            let value = value.trim();
+            match label {
+                "Acceleration" => Ok(race.clone().with_acceleration(parse_field(line, value)?)),
+                "Max speed" => Ok(race.clone().with_max_speed(parse_field(line, value)?)),
+                "Start delay" => Ok(race.clone().with_start_delay(parse_field(line, value)?)),
+                "Boost" => {
+                    let (extra, duration) = value.split_once(" for ").ok_or_else(|| {
+                        ParseError::at(line, value, "expected `<extra> for <duration>`")
+                    })?;
+                    let (extra, duration) =
+                        (parse_field(line, extra)?, parse_field(line, duration)?);
+                    Ok(race.clone().with_boost(extra, duration))
+                }
+                _ => Err(ParseError::at(
+                    line,
+                    label,
+                    "expected `Acceleration`, `Max speed`, `Start delay` or `Boost`",
+                )),
+            }
+        };
+        race = setting().map_err(|e| e.on_line(n + 1).in_day(6))?;
+    }
+    Ok(race)
+}
+
+impl Race {
+    pub fn new(time: usize, distance: usize) -> Self {
         Self {
-            time: value.0,
-            distance: value.1,
+            time,
+            distance,
+            acceleration: 1,
+            max_speed: None,
+            start_delay: 0,
+            boosts: Vec::new(),
         }
     }
-}
 
-fn parse_line_numbers(input: &str) -> Vec<usize> {
-    str_numbers_to_collecatble(&input[9..]).unwrap()
+    /// Speed gained per ms the button is held, 1 by default.
+    pub fn with_acceleration(self, acceleration: usize) -> Self {
+        Self {
+            acceleration,
+            ..self
+        }
+    }
+
+    pub fn with_max_speed(self, max_speed: usize) -> Self {
+        Self {
+            max_speed: Some(max_speed),
+            ..self
+        }
+    }
+
+    /// Time between releasing the button and the boat starting to move.
+    pub fn with_start_delay(self, start_delay: usize) -> Self {
+        Self {
+            start_delay,
+            ..self
+        }
+    }
+
+    /// Adds a boost, boosts stack while they last.
+    pub fn with_boost(mut self, extra: usize, duration: usize) -> Self {
+        self.boosts.push(Boost { extra, duration });
+        self
+    }
+
+    /// How far the boat gets when the button is held for `hold` ms.
+    pub fn distance_for(&self, hold: usize) -> u128 {
+        let moving = self
+            .time
+            .saturating_sub(hold)
+            .saturating_sub(self.start_delay);
+        let boosted = self
+            .boosts
+            .iter()
+            .map(|boost| boost.extra as u128 * hold.min(boost.duration) as u128);
+        let speed = self.acceleration as u128 * hold as u128 + boosted.sum::<u128>();
+        let speed = self.max_speed.map_or(speed, |max| speed.min(max as u128));
+        speed * moving as u128
+    }
+
+    fn beats_record(&self, hold: usize) -> bool {
+        self.distance_for(hold) > self.distance as u128
+    }
+
+    /// The hold times beating the record, `None` if there are none.
+    pub fn winning_holds(&self) -> Option<RangeInclusive<usize>> {
+        let time = self.time.checked_sub(self.start_delay)?;
+        match self.max_speed {
+            // `acceleration * hold * (time - hold)` beats the record exactly
+            // when `hold * (time - hold)` beats it divided by the acceleration
+            None if self.boosts.is_empty() && self.acceleration > 0 => {
+                Race::new(time, self.distance / self.acceleration).quadratic_holds()
+            }
+            _ => self.searched_holds(time),
+        }
+    }
+
+    /// [`Race::winning_holds`] of a race without any of the extra physics.
+    ///
+    /// The distance `hold * (time - hold)` beats the record between the roots
+    /// of `hold² - time * hold + distance`, found with an integer square root
+    /// so there is no rounding however large the race.
+    fn quadratic_holds(&self) -> Option<RangeInclusive<usize>> {
+        let (time, distance) = (self.time as u128, self.distance as u128);
+        let discriminant = (time * time).checked_sub(4 * distance)?;
+        // the root may be off by one either way, step to the first winner
+        let mut low = ((time - discriminant.isqrt()) / 2) as usize;
+        while low > 0 && self.beats_record(low - 1) {
+            low -= 1;
+        }
+        while low <= self.time / 2 && !self.beats_record(low) {
+            low += 1;
+        }
+        // the distance is symmetric around `time / 2`
+        (low <= self.time / 2).then(|| low..=self.time - low)
+    }
+
+    /// [`Race::winning_holds`] with boosts or a speed cap, `time` being the
+    /// time left after the start delay.
+    ///
+    /// Each ms of holding gains no more speed than the one before, as boosts
+    /// only wear off and the cap only stops the gain, while the boat loses a
+    /// ms of moving. So the distance gains less with every ms held, rising up
+    /// to the best hold and only falling after it, and the best hold and both
+    /// ends of the window are found by binary search.
+    fn searched_holds(&self, time: usize) -> Option<RangeInclusive<usize>> {
+        let best = partition_point(0..time, |hold| {
+            self.distance_for(hold + 1) > self.distance_for(hold)
+        });
+        if !self.beats_record(best) {
+            return None;
+        }
+        let low = partition_point(0..best, |hold| !self.beats_record(hold));
+        let high = partition_point(best..time + 1, |hold| self.beats_record(hold));
+        Some(low..=high - 1)
+    }
 }
 
-fn parse_line_bad_kerning(input: &str) -> usize {
-    input[9..].trim().replace(' ', "").parse().unwrap()
+/// The first value of `range` for which `pred` is false, `pred` being true
+/// up to some point and false after it.
+fn partition_point(range: Range<usize>, pred: impl Fn(usize) -> bool) -> usize {
+    let (mut low, mut high) = (range.start, range.end);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
 }
 
 fn winning_times(race: &Race) -> usize {
-    // use the qadratic equation to solve this
-    // a = -1
-    // b = race.time (t)
-    // c = -race.distance (d)
-    let t = race.time as f64;
-    // we need beat the record, not tangent it
-    // 0.1 is ok as distances we are looking for will always be whole numbers
-    let d = race.distance as f64 + 0.1;
-    let root = (t.powi(2) - 4. * d).sqrt();
-    let a = (((-t - root) / -2.).floor()) as usize;
-    let b = (((-t + root) / -2.).ceil()) as usize;
-    a - b + 1
+    race.winning_holds()
+        .map_or(0, |holds| holds.end() - holds.start() + 1)
 }
 
-#[aoc_generator(day6, part1)]
-fn parse_part1(input: &str) -> Vec<Race> {
-    let (time_str, distance_str) = input.split_once('\n').unwrap();
-    parse_line_numbers(time_str)
+fn parse_part1(input: &str) -> Result<Vec<Race>, ParseError> {
+    let (time_str, distance_str) = split_lines(input)?;
+    let times = parse_line_numbers(time_str).map_err(|e| e.on_line(1).in_day(6))?;
+    let distances = parse_line_numbers(distance_str).map_err(|e| e.on_line(2).in_day(6))?;
+    if times.len() != distances.len() {
+        return Err(ParseError::at(
+            distance_str,
+            distance_str,
+            format!(
+                "expected {} distances, found {}",
+                times.len(),
+                distances.len()
+            ),
+        )
+        .on_line(2)
+        .in_day(6));
+    }
+    times
         .into_iter()
-        .zip(parse_line_numbers(distance_str))
-        .map(Race::from)
+        .zip(distances)
+        .map(|race| with_physics(input, Race::from(race)))
         .collect()
 }
 
-#[aoc_generator(day6, part2)]
-fn parse_part2(input: &str) -> Race {
-    let (time_str, distance_str) = input.split_once('\n').unwrap();
-    Race {
-        time: parse_line_bad_kerning(time_str),
-        distance: parse_line_bad_kerning(distance_str),
-    }
+fn parse_part2(input: &str) -> Result<Race, ParseError> {
+    let (time_str, distance_str) = split_lines(input)?;
+    let race = Race::new(
+        parse_line_bad_kerning(time_str).map_err(|e| e.on_line(1).in_day(6))?,
+        parse_line_bad_kerning(distance_str).map_err(|e| e.on_line(2).in_day(6))?,
+    );
+    with_physics(input, race)
 }
 
-#[aoc(day6, part1)]
 fn part1(input: &[Race]) -> usize {
     input.iter().map(winning_times).product()
 }
 
-#[aoc(day6, part2)]
 fn part2(race: &Race) -> usize {
     winning_times(race)
 }
 
-fn str_numbers_to_collecatble<T>(s: &str) -> Result<T, ParseIntError>
-where
-    T: FromIterator<usize>,
-{
-    s.trim()
-        .split(' ')
-        .filter(|n| !n.is_empty())
-        .map(str::trim)
-        .map(usize::from_str)
-        .collect()
-}
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(6, 1, |input| timed(input, parse_part1, part1)),
+    Solution::new(6, 2, |input| timed(input, parse_part2, part2)),
+];
 
 #[cfg(test)]
 mod tests {
-    use super::*;
     use indoc::indoc;
+    use proptest::prelude::*;
+
+    use super::*;
 
     static INPUT: &str = indoc! {"
         Time:      7  15   30
@@ -93,11 +276,147 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse_part1(INPUT)), 288);
+        assert_eq!(part1(&parse_part1(INPUT).unwrap()), 288);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse_part2(INPUT)), 71503);
+        assert_eq!(part2(&parse_part2(INPUT).unwrap()), 71503);
+    }
+
+    #[test]
+    fn test_winning_holds() {
+        let race = Race::new;
+        assert_eq!(race(7, 9).winning_holds(), Some(2..=5));
+        assert_eq!(race(30, 200).winning_holds(), Some(11..=19));
+        // tangent records can't be beaten
+        assert_eq!(race(4, 4).winning_holds(), None);
+        assert_eq!(race(5, 6).winning_holds(), None);
+        assert_eq!(race(5, 5).winning_holds(), Some(2..=3));
+        assert_eq!(race(0, 0).winning_holds(), None);
+        assert_eq!(race(3, 100).winning_holds(), None);
+        assert_eq!(winning_times(&race(3, 100)), 0);
+
+        // far beyond the precision of f64
+        let k = 1_000_000_007;
+        assert_eq!(race(2 * k, k * k - 1).winning_holds(), Some(k..=k));
+        assert_eq!(race(2 * k, k * k).winning_holds(), None);
+        assert_eq!(
+            race(2 * k + 1, k * (k + 1) - 1).winning_holds(),
+            Some(k..=k + 1)
+        );
+    }
+
+    #[test]
+    fn test_physics() {
+        let race = Race::new(10, 20).with_acceleration(2);
+        assert_eq!(race.distance_for(3), 42);
+        assert_eq!(race.winning_holds(), Some(2..=8));
+        let race = race.with_max_speed(6);
+        assert_eq!(race.distance_for(5), 30);
+        assert_eq!(race.winning_holds(), Some(2..=6));
+        let race = race.with_start_delay(3);
+        assert_eq!(race.distance_for(3), 24);
+        assert_eq!(race.winning_holds(), Some(3..=3));
+        assert_eq!(Race::new(10, 0).with_acceleration(0).winning_holds(), None);
+        assert_eq!(Race::new(3, 0).with_start_delay(5).winning_holds(), None);
+    }
+
+    #[test]
+    fn test_boosts() {
+        let race = Race::new(10, 40).with_boost(3, 2).with_boost(1, 4);
+        // gaining 1 + 3 + 1 mm/ms for 2 ms, 1 + 1 for 2 more and 1 after that
+        assert_eq!(race.distance_for(2), 10 * 8);
+        assert_eq!(race.distance_for(5), 15 * 5);
+        assert_eq!(race.winning_holds(), Some(1..=7));
+        assert_eq!(race.clone().with_max_speed(8).winning_holds(), Some(1..=4));
+        let race = Race::new(10, 0).with_acceleration(0).with_boost(2, 1);
+        assert_eq!(race.distance_for(3), 2 * 7);
+        assert_eq!(race.winning_holds(), Some(1..=9));
+    }
+
+    #[test]
+    fn parse_physics() {
+        let input = "Time: 10 12\nDistance: 20 1\nAcceleration: 2\nMax speed: 6";
+        let races = parse_part1(input).unwrap();
+        assert_eq!(
+            races[1],
+            Race::new(12, 1).with_acceleration(2).with_max_speed(6)
+        );
+        assert_eq!(winning_times(&races[0]), 5);
+        let race = parse_part2("Time: 1 0\nDistance: 2 0\nStart delay: 1").unwrap();
+        assert_eq!(race, Race::new(10, 20).with_start_delay(1));
+
+        let error = parse_part2("Time: 1\nDistance: 2\nTop speed: 1").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(3), Some(1)));
+        let error = parse_part1("Time: 1\nDistance: 2\nMax speed: x").unwrap_err();
+        assert_eq!((error.line, error.text.as_str()), (Some(3), "x"));
+
+        let input = "Time: 10\nDistance: 40\nBoost: 3 for 2\nBoost: 1 for 4";
+        let race = parse_part2(input).unwrap();
+        assert_eq!(race, Race::new(10, 40).with_boost(3, 2).with_boost(1, 4));
+        let error = parse_part2("Time: 10\nDistance: 40\nBoost: 3").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(3), Some(8)));
+    }
+
+    fn brute_force(race: &Race) -> usize {
+        (0..=race.time)
+            .filter(|&hold| race.beats_record(hold))
+            .count()
+    }
+
+    proptest! {
+        #[test]
+        fn winning_times_is_brute_force(time in 0..300_usize, distance in 0..25_000_usize) {
+            let race = Race::new(time, distance);
+            prop_assert_eq!(winning_times(&race), brute_force(&race));
+        }
+
+        #[test]
+        fn winning_holds_are_brute_force(
+            time in 0..200_usize,
+            distance in 0..3_000_usize,
+            acceleration in 0..4_usize,
+            max_speed in prop::option::of(0..60_usize),
+            start_delay in 0..10_usize,
+            boosts in prop::collection::vec((0..5_usize, 0..20_usize), 0..3),
+        ) {
+            let mut race = Race::new(time, distance)
+                .with_acceleration(acceleration)
+                .with_start_delay(start_delay);
+            for (extra, duration) in boosts {
+                race = race.with_boost(extra, duration);
+            }
+            if let Some(max_speed) = max_speed {
+                race = race.with_max_speed(max_speed);
+            }
+            let winners = (0..=time).filter(|&hold| race.beats_record(hold)).collect::<Vec<_>>();
+            let expected = winners.first().zip(winners.last()).map(|(&low, &high)| low..=high);
+            prop_assert_eq!(winners.len(), expected.clone().map_or(0, |r| r.count()));
+            prop_assert_eq!(race.winning_holds(), expected);
+        }
+
+        #[test]
+        fn tangent_records_are_exact(hold in 1..3_000_000_000_usize, extra in 0..2_usize) {
+            let time = 2 * hold + extra;
+            let best = hold * (time - hold);
+            prop_assert_eq!(winning_times(&Race::new(time, best)), 0);
+            let holds = Race::new(time, best - 1).winning_holds();
+            let expected = if extra % 2 == 0 { hold..=hold } else { hold..=hold + 1 };
+            prop_assert_eq!(holds, Some(expected));
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse_part1("Time:      7  15   30\nDistance:  9  4O  200").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(15)));
+        assert_eq!(error.text, "4O");
+
+        let error = parse_part1("Time:      7  15   30\nDistance:  9  40").unwrap_err();
+        assert_eq!(error.line, Some(2));
+
+        let error = parse_part2("Time:      7  15   30").unwrap_err();
+        assert_eq!(error.day, Some(6));
     }
 }
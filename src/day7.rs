@@ -1,189 +1,302 @@
-use std::{cmp::Ordering, num::ParseIntError, str::FromStr};
+use std::{borrow::Cow, error::Error, fmt::Display};
 
-use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
-#[derive(Debug)]
-struct Hand {
-    cards: [char; 5],
-    bid: usize,
-}
+use crate::{
+    error::{parse_field, parse_lines, ParseError},
+    format::{self, Align, Format},
+    runner::{timed, Solution},
+};
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-enum HandType {
-    HighCard,
-    OnePair,
-    TwoPair,
-    ThreeOfAKind,
-    FullHouse,
-    FourOfAKind,
-    FiveOfAKind,
+#[derive(Debug, PartialEq, Eq)]
+pub struct Hand {
+    pub cards: Vec<char>,
+    pub bid: usize,
 }
 
-use HandType::*;
+/// The sizes of the groups of equal cards, largest first. Comparing them
+/// lexicographically gives the usual lattice for any hand size, from high
+/// card up to all cards alike.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HandType(Vec<usize>);
 
 impl HandType {
-    fn from_hand_p1(hand: &Hand) -> Self {
-        let cards = hand.cards.iter().copied().counts();
-        let mut selsections = cards.values().sorted().rev();
-        match selsections.next().unwrap() {
-            5 => FiveOfAKind,
-            4 => FourOfAKind,
-            3 => match selsections.next().unwrap() {
-                2 => FullHouse,
-                1 => ThreeOfAKind,
-                _ => unreachable!("More than 5 cards in one hand"),
-            },
-            2 => match selsections.next().unwrap() {
-                2 => TwoPair,
-                1 => OnePair,
-                _ => unreachable!("More than 5 cards in one hand"),
-            },
-            1 => HighCard,
-            _ => unreachable!("More than 5 cards in one hand"),
+    /// The best type `cards` can make under `rules`, every wildcard joining
+    /// the largest group of other cards.
+    fn of(cards: &[char], rules: &Rules) -> Self {
+        let mut groups = cards
+            .iter()
+            .filter(|card| !rules.is_wild(**card))
+            .counts()
+            .into_values()
+            .sorted()
+            .rev()
+            .collect_vec();
+        let wild = cards.len() - groups.iter().sum::<usize>();
+        match groups.first_mut() {
+            Some(largest) => *largest += wild,
+            None => groups.push(wild),
         }
+        Self(groups)
     }
+}
 
-    fn from_hand_p2(hand: &Hand) -> Self {
-        let cards = hand.cards.iter().copied().counts();
-        let mut selsections = cards.into_iter().sorted_by_key(|n| n.1).rev();
-        match selsections.next().unwrap() {
-            (_, 5) => FiveOfAKind,
-            (v0, 4) => {
-                if v0 == 'J' || selsections.next().unwrap().0 == 'J' {
-                    FiveOfAKind
-                } else {
-                    FourOfAKind
+impl Display for HandType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let groups = self.0.iter().filter(|&&size| size > 1).collect_vec();
+        match groups[..] {
+            [] => write!(f, "high card"),
+            [2] => write!(f, "one pair"),
+            [2, 2] => write!(f, "two pair"),
+            [3, 2] => write!(f, "full house"),
+            [3, 3] => write!(f, "two triples"),
+            [&size] => {
+                let names = ["three", "four", "five", "six", "seven", "eight", "nine"];
+                match names.get(size - 3) {
+                    Some(name) => write!(f, "{name} of a kind"),
+                    None => write!(f, "{size} of a kind"),
                 }
             }
-            (v0, 3) => match selsections.next().unwrap() {
-                (v1, 2) => {
-                    if v0 == 'J' || v1 == 'J' {
-                        FiveOfAKind
-                    } else {
-                        FullHouse
-                    }
-                }
-                (v1, 1) => {
-                    if v0 == 'J' || v1 == 'J' || selsections.next().unwrap().0 == 'J' {
-                        FourOfAKind
-                    } else {
-                        ThreeOfAKind
-                    }
-                }
-                (_v, _) => unreachable!("More than 5 cards in one hand"),
-            },
-            (v0, 2) => match selsections.next().unwrap() {
-                (v1, 2) => match selsections.next().unwrap().0 {
-                    'J' => FullHouse,
-                    _ => {
-                        if v0 == 'J' || v1 == 'J' {
-                            FourOfAKind
-                        } else {
-                            TwoPair
-                        }
-                    }
-                },
-                (v1, 1) => {
-                    if v0 == 'J' || v1 == 'J' || selsections.any(|n| n.0 == 'J') {
-                        ThreeOfAKind
-                    } else {
-                        OnePair
-                    }
-                }
-                (_v, _) => unreachable!("More than 5 cards in one hand"),
-            },
-            (v0, 1) => {
-                if v0 == 'J' || selsections.any(|n| n.0 == 'J') {
-                    OnePair
-                } else {
-                    HighCard
-                }
-            }
-            (_v, _) => unreachable!("More than 5 cards in one hand"),
+            _ => write!(f, "groups of {}", groups.iter().join(", ")),
         }
     }
 }
 
-impl FromStr for Hand {
-    type Err = ParseIntError;
+/// What is wrong with the cards of a hand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandError {
+    /// Not the number of cards the rules deal.
+    Size { expected: usize, found: usize },
+    /// A card missing from the rules' order, with its byte offset.
+    UnknownCard { card: char, at: usize },
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (chars, bid) = s.trim().split_once(' ').unwrap();
-        let mut cards = [' '; 5];
-        chars.char_indices().for_each(|(n, c)| cards[n] = c);
-        Ok(Self {
-            cards,
-            bid: bid.parse()?,
-        })
+impl Display for HandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Size { expected, found } => write!(f, "expected {expected} cards, found {found}"),
+            Self::UnknownCard { card, .. } => write!(f, "unknown card `{card}`"),
+        }
     }
 }
 
-const ORDER_P1: [char; 13] = [
-    'A', 'K', 'Q', 'J', 'T', '9', '8', '7', '6', '5', '4', '3', '2',
-];
-const ORDER_P2: [char; 13] = [
-    'A', 'K', 'Q', 'T', '9', '8', '7', '6', '5', '4', '3', '2', 'J',
-];
+impl Error for HandError {}
 
-fn char_to_value_p1(c: &char) -> usize {
-    13 - ORDER_P1.iter().find_position(|&n| n == c).unwrap().0
+/// How cards rank and which of them are wild.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// Every card, strongest first.
+    pub order: Cow<'static, str>,
+    /// Cards standing in for whatever makes the best hand type. They still
+    /// rank by `order` when hands of the same type are compared.
+    pub wildcards: Cow<'static, str>,
+    /// Cards in a hand.
+    pub size: usize,
 }
 
-fn char_to_value_p2(c: &char) -> usize {
-    13 - ORDER_P2.iter().find_position(|&n| n == c).unwrap().0
+/// Camel Cards without jokers.
+const STANDARD: Rules = Rules {
+    order: Cow::Borrowed("AKQJT98765432"),
+    wildcards: Cow::Borrowed(""),
+    size: 5,
+};
+
+/// `J` is a joker, the weakest card but wild.
+const JOKERS: Rules = Rules {
+    order: Cow::Borrowed("AKQT98765432J"),
+    wildcards: Cow::Borrowed("J"),
+    size: 5,
+};
+
+/// What hands are ranked by, computed once per hand.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct SortKey {
+    hand_type: HandType,
+    strengths: Vec<usize>,
 }
 
-fn sort_hands_by_p1(l: &&Hand, r: &&Hand) -> Ordering {
-    HandType::from_hand_p1(l)
-        .cmp(&HandType::from_hand_p1(r))
-        .then_with(|| {
-            l.cards
-                .iter()
-                .map(char_to_value_p1)
-                .cmp(r.cards.iter().map(char_to_value_p1))
+impl Rules {
+    fn is_wild(&self, card: char) -> bool {
+        self.wildcards.contains(card)
+    }
+
+    /// Higher is stronger.
+    fn strength(&self, card: char) -> usize {
+        let position = self.order.chars().position(|c| c == card);
+        self.order.chars().count() - position.expect("cards are checked when parsed")
+    }
+
+    /// Checks that `cards` is a hand under these rules.
+    pub fn cards(&self, cards: &str) -> Result<Vec<char>, HandError> {
+        if let Some((at, card)) = cards.char_indices().find(|&(_, c)| !self.order.contains(c)) {
+            return Err(HandError::UnknownCard { card, at });
+        }
+        let cards = cards.chars().collect_vec();
+        if cards.len() != self.size {
+            return Err(HandError::Size {
+                expected: self.size,
+                found: cards.len(),
+            });
+        }
+        Ok(cards)
+    }
+
+    /// Parses one `<cards> <bid>` line.
+    fn hand(&self, line: &str) -> Result<Hand, ParseError> {
+        let (cards, bid) = line
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| ParseError::at(line, line, "expected `<cards> <bid>`"))?;
+        let cards = self.cards(cards).map_err(|e| {
+            let part = match e {
+                HandError::UnknownCard { card, at } => &cards[at..at + card.len_utf8()],
+                HandError::Size { .. } => cards,
+            };
+            ParseError::at(line, part, e.to_string())
+        })?;
+        Ok(Hand {
+            cards,
+            bid: parse_field(line, bid)?,
         })
+    }
+
+    /// Parses one hand per line, checking the cards against these rules.
+    pub fn parse(&self, input: &str) -> Result<Vec<Hand>, ParseError> {
+        parse_lines(7, input, |line| self.hand(line))
+    }
+
+    fn key(&self, hand: &Hand) -> SortKey {
+        SortKey {
+            hand_type: HandType::of(&hand.cards, self),
+            strengths: hand.cards.iter().map(|&card| self.strength(card)).collect(),
+        }
+    }
+
+    /// `cards` with every wildcard replaced by the card it stands in for,
+    /// the strongest of the most common other cards.
+    pub fn substitute(&self, cards: &[char]) -> Vec<char> {
+        let counts = cards.iter().filter(|card| !self.is_wild(**card)).counts();
+        let target = counts
+            .into_iter()
+            .max_by_key(|&(&card, count)| (count, self.strength(card)))
+            .map(|(&card, _)| card)
+            .or_else(|| self.order.chars().next())
+            .unwrap_or_default();
+        cards
+            .iter()
+            .map(|&card| if self.is_wild(card) { target } else { card })
+            .collect()
+    }
+
+    /// `hands` from weakest to strongest. Hands with the same cards keep
+    /// their input order and are marked as tied.
+    pub fn rank<'a>(&self, hands: &'a [Hand]) -> Vec<Ranked<'a>> {
+        let keyed = hands
+            .iter()
+            .map(|hand| (self.key(hand), hand))
+            .sorted_by(|(l, _), (r, _)| l.cmp(r))
+            .collect_vec();
+        let same_key = |n: usize, m: Option<usize>| {
+            m.and_then(|m| keyed.get(m))
+                .is_some_and(|(key, _)| *key == keyed[n].0)
+        };
+        keyed
+            .iter()
+            .enumerate()
+            .map(|(n, (key, hand))| Ranked {
+                hand,
+                hand_type: key.hand_type.clone(),
+                substituted: self.substitute(&hand.cards),
+                rank: n + 1,
+                winnings: (n + 1) * hand.bid,
+                tied: same_key(n, n.checked_sub(1)) || same_key(n, Some(n + 1)),
+            })
+            .collect()
+    }
+
+    /// Total winnings of `hands`, each winning its bid times its rank.
+    pub fn winnings(&self, hands: &[Hand]) -> usize {
+        self.rank(hands).iter().map(|ranked| ranked.winnings).sum()
+    }
 }
 
-fn sort_hands_by_p2(l: &&Hand, r: &&Hand) -> Ordering {
-    HandType::from_hand_p2(l)
-        .cmp(&HandType::from_hand_p2(r))
-        .then_with(|| {
-            l.cards
-                .iter()
-                .map(char_to_value_p2)
-                .cmp(r.cards.iter().map(char_to_value_p2))
-        })
+/// A hand's place in the ranking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ranked<'a> {
+    pub hand: &'a Hand,
+    pub hand_type: HandType,
+    /// The cards the hand type is made of, see [`Rules::substitute`].
+    pub substituted: Vec<char>,
+    pub rank: usize,
+    pub winnings: usize,
+    /// Another hand has the very same cards, their ranks only follow the
+    /// input order.
+    pub tied: bool,
+}
+
+/// The ranked hands of `input` under the rules of `part`, with their types,
+/// wildcard substitutions and winnings.
+pub fn report(input: &str, part: u32, format: Format) -> Result<String, ParseError> {
+    let rules = if part == 1 { STANDARD } else { JOKERS };
+    Ok(table(&rules.rank(&parse(input)?), format))
 }
 
-#[aoc_generator(day7)]
-fn parse(input: &str) -> Vec<Hand> {
-    input.lines().map(Hand::from_str).try_collect().unwrap()
+fn table(ranked: &[Ranked], format: Format) -> String {
+    let rows = ranked.iter().map(|ranked| {
+        [
+            ranked.rank.to_string(),
+            ranked.hand.cards.iter().collect(),
+            ranked.hand_type.to_string(),
+            ranked.substituted.iter().collect(),
+            ranked.hand.bid.to_string(),
+            ranked.winnings.to_string(),
+            if ranked.tied { "tie" } else { "" }.to_owned(),
+        ]
+    });
+    let header = ["rank", "hand", "type", "as", "bid", "winnings", "tied"].map(str::to_owned);
+    let rows = [header]
+        .into_iter()
+        .chain(rows)
+        .map(Vec::from)
+        .collect_vec();
+    let align = |column| match column {
+        1..=3 | 6 => Align::Left,
+        _ => Align::Right,
+    };
+    let mut report = format::render(&rows, format, align);
+    if format == Format::Table {
+        let total: usize = ranked.iter().map(|ranked| ranked.winnings).sum();
+        let ties = ranked.iter().filter(|ranked| ranked.tied).count();
+        report += &format!("\n\ntotal winnings {total}");
+        if ties > 0 {
+            report += &format!(", {ties} tied hands ranked in input order");
+        }
+    }
+    report
+}
+
+/// Both parts deal the same cards, so either rules check them.
+fn parse(input: &str) -> Result<Vec<Hand>, ParseError> {
+    STANDARD.parse(input)
 }
 
-#[aoc(day7, part1)]
 fn part1(hands: &[Hand]) -> usize {
-    hands
-        .iter()
-        .sorted_by(sort_hands_by_p1)
-        .enumerate()
-        .map(|(n, hand)| (n + 1) * hand.bid)
-        .sum()
+    STANDARD.winnings(hands)
 }
 
-#[aoc(day7, part2)]
 fn part2(hands: &[Hand]) -> usize {
-    hands
-        .iter()
-        .sorted_by(sort_hands_by_p2)
-        .enumerate()
-        .map(|(n, hand)| (n + 1) * hand.bid)
-        .sum()
+    JOKERS.winnings(hands)
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(7, 1, |input| timed(input, parse, part1)),
+    Solution::new(7, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
+    use proptest::prelude::*;
 
     use super::*;
 
@@ -197,11 +310,209 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(INPUT)), 6440);
+        assert_eq!(part1(&parse(INPUT).unwrap()), 6440);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(INPUT)), 5905);
+        assert_eq!(part2(&parse(INPUT).unwrap()), 5905);
+    }
+
+    #[test]
+    fn test_hand_types() {
+        let of = |cards: &str, rules| HandType::of(&cards.chars().collect_vec(), rules).to_string();
+        assert_eq!(of("KTJJT", &STANDARD), "two pair");
+        assert_eq!(of("KTJJT", &JOKERS), "four of a kind");
+        assert_eq!(of("JJJJJ", &JOKERS), "five of a kind");
+        assert_eq!(of("2345J", &JOKERS), "one pair");
+        assert_eq!(of("23456", &JOKERS), "high card");
+        assert_eq!(of("QQQJA", &STANDARD), "three of a kind");
+        assert_eq!(of("QQQJJ", &STANDARD), "full house");
+
+        let deuces = Rules {
+            order: "AKQJT98765432".into(),
+            wildcards: "2".into(),
+            size: 5,
+        };
+        assert_eq!(of("22AK7", &deuces), "three of a kind");
+        assert_eq!(of("KTJJT", &deuces), "two pair");
+
+        let two_jokers = Rules {
+            order: "AKQJT98765432*?".into(),
+            wildcards: "*?".into(),
+            size: 5,
+        };
+        assert_eq!(of("*?A3A", &two_jokers), "four of a kind");
+        let hands = two_jokers.parse("*?A3A 2\nAAA3A 3").unwrap();
+        assert_eq!(two_jokers.winnings(&hands), 2 + 3 * 2);
+    }
+
+    #[test]
+    fn test_rank() {
+        let hands = parse(INPUT).unwrap();
+        let ranked = JOKERS.rank(&hands);
+        let ranked = ranked.last().unwrap();
+        assert_eq!(ranked.hand.cards.iter().collect::<String>(), "KTJJT");
+        assert_eq!(ranked.substituted.iter().collect::<String>(), "KTTTT");
+        assert_eq!(
+            (ranked.rank, ranked.winnings, ranked.tied),
+            (5, 1100, false)
+        );
+        assert_eq!(JOKERS.substitute(&['J'; 5]), vec!['A'; 5]);
+        assert_eq!(STANDARD.substitute(&hands[0].cards), hands[0].cards);
+
+        let hands = parse("QQQJA 1\nT55J5 2\nQQQJA 3").unwrap();
+        let ranked = STANDARD.rank(&hands);
+        assert_eq!(
+            ranked.iter().map(|r| (r.hand.bid, r.tied)).collect_vec(),
+            vec![(2, false), (1, true), (3, true)]
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let table = report(INPUT, 2, Format::Table).unwrap();
+        assert!(table.starts_with(indoc! {"
+            rank  hand   type            as     bid  winnings  tied
+               1  32T3K  one pair        32T3K  765       765
+        "}));
+        assert!(table.contains("\n   5  KTJJT  four of a kind  KTTTT  220      1100\n"));
+        assert!(table.ends_with("\n\ntotal winnings 5905"));
+
+        let table = report("QQQJA 1\nQQQJA 3", 1, Format::Table).unwrap();
+        assert!(table.contains("   2  QQQJA  three of a kind  QQQJA    3         6  tie\n"));
+        assert!(table.ends_with(", 2 tied hands ranked in input order"));
+
+        let csv = report(INPUT, 1, Format::Csv).unwrap();
+        assert!(csv
+            .starts_with("rank,hand,type,as,bid,winnings,tied\n1,32T3K,one pair,32T3K,765,765,\n"));
+        let json = report(INPUT, 1, Format::Json).unwrap();
+        assert!(json.contains(
+            "{\"rank\": 1, \"hand\": \"32T3K\", \"type\": \"one pair\", \"as\": \"32T3K\", \
+             \"bid\": 765, \"winnings\": 765, \"tied\": \"\"}"
+        ));
+    }
+
+    #[test]
+    fn test_runtime_rules() {
+        // aces low, read from somewhere at runtime
+        let order: String = STANDARD.order.chars().rev().collect();
+        let rules = Rules {
+            order: order.into(),
+            wildcards: String::from("K").into(),
+            size: 5,
+        };
+        let hands = rules.parse("AAAA2 1\n2222A 2\nKKKKK 3").unwrap();
+        assert_eq!(rules.winnings(&hands), 1 + 2 * 2 + 3 * 3);
+        assert_eq!(
+            rules.substitute(&['K', '2', 'A', '2', '3']),
+            vec!['2', '2', 'A', '2', '3']
+        );
+    }
+
+    #[test]
+    fn test_unicode_rules() {
+        let rules = Rules {
+            order: "♠♥♦♣".into(),
+            wildcards: "♣".into(),
+            size: 3,
+        };
+        assert_eq!((rules.strength('♠'), rules.strength('♣')), (4, 1));
+        let hands = rules.parse("♠♥♦ 10\n♥♥♣ 1").unwrap();
+        assert_eq!(rules.winnings(&hands), 10 + 2);
+        assert!(
+            table(&rules.rank(&hands), Format::Table).starts_with(indoc! {"
+            rank  hand  type             as   bid  winnings  tied
+               1  ♠♥♦   high card        ♠♥♦   10        10
+               2  ♥♥♣   three of a kind  ♥♥♥    1         2
+        "})
+        );
+    }
+
+    #[test]
+    fn test_hand_sizes() {
+        let six = Rules {
+            order: "AKQJT98765432".into(),
+            wildcards: "J".into(),
+            size: 6,
+        };
+        let of = |cards: &str| HandType::of(&cards.chars().collect_vec(), &six);
+        assert_eq!(of("AAAJAA").to_string(), "six of a kind");
+        assert_eq!(of("AAAKKK").to_string(), "two triples");
+        assert_eq!(of("AAKKQQ").to_string(), "groups of 2, 2, 2");
+        assert!(of("AAAKKK") > of("AAAKKQ"));
+        assert!(of("AAAKKQ") > of("AAAKQT"));
+        assert!(of("AAAKQT") > of("AAKKQQ"));
+        assert!(of("AAAAK2") < of("AAAAKK"));
+
+        let hands = six.parse("AAAKKK 10\nKKKKAJ 1\n234567 5").unwrap();
+        assert_eq!(six.winnings(&hands), 5 + 2 * 10 + 3);
+        let error = six.parse("AAAKKK 10\nAAAAA 1").unwrap_err();
+        assert_eq!(
+            (error.line, error.reason.as_str()),
+            (Some(2), "expected 6 cards, found 5")
+        );
+    }
+
+    /// The best type over every way of replacing the wildcards.
+    fn brute_force(cards: &[char], rules: &Rules) -> HandType {
+        let plain = Rules {
+            wildcards: "".into(),
+            ..rules.clone()
+        };
+        let choices = cards.iter().map(|&card| {
+            if rules.is_wild(card) {
+                rules.order.chars().collect_vec()
+            } else {
+                vec![card]
+            }
+        });
+        choices
+            .multi_cartesian_product()
+            .map(|cards| HandType::of(&cards, &plain))
+            .max()
+            .unwrap()
+    }
+
+    proptest! {
+        #[test]
+        fn best_type_is_brute_force(cards in prop::collection::vec(prop::sample::select(vec!['A', 'K', 'Q', 'J', '2']), 5)) {
+            let rules = Rules {
+                order: "AKQJ2".into(),
+                wildcards: "J".into(),
+                size: 5,
+            };
+            prop_assert_eq!(HandType::of(&cards, &rules), brute_force(&cards, &rules));
+            let rules = Rules {
+                order: "AKQJ2".into(),
+                wildcards: "J2".into(),
+                size: 5,
+            };
+            prop_assert_eq!(HandType::of(&cards, &rules), brute_force(&cards, &rules));
+            let plain = Rules { wildcards: "".into(), ..rules.clone() };
+            prop_assert_eq!(HandType::of(&rules.substitute(&cards), &plain), HandType::of(&cards, &rules));
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse("32T3K 765\nT55J5 68A").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(7)));
+
+        let error = parse("32T3 765").unwrap_err();
+        assert_eq!((error.column, error.text.as_str()), (Some(1), "32T3"));
+
+        let error = parse("32T3K").unwrap_err();
+        assert_eq!(error.line, Some(1));
+
+        let error = parse("32T3K 765\n3xT3K 1").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(2)));
+        assert_eq!(error.reason, "unknown card `x`");
+
+        let error = parse("32T3K3 765").unwrap_err();
+        assert_eq!(error.reason, "expected 5 cards, found 6");
+
+        let error = JOKERS.cards("★2345").unwrap_err();
+        assert_eq!(error, HandError::UnknownCard { card: '★', at: 0 });
     }
 }
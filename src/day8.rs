@@ -1,33 +1,57 @@
 use std::collections::HashMap;
 
+use crate::{
+    error::ParseError,
+    runner::{timed, Solution},
+};
+
 struct Map<'a> {
     movements: Vec<usize>, // 0 = Left, 1 = Right
     spots: HashMap<&'a str, [&'a str; 2]>,
 }
 
 impl<'a> TryFrom<&'a str> for Map<'a> {
-    type Error = ();
+    type Error = ParseError;
 
     fn try_from(s: &'a str) -> Result<Self, Self::Error> {
-        let (movements, spots) = s.split_once("\n\n").unwrap();
+        let (movements, spots) = s
+            .split_once("\n\n")
+            .ok_or_else(|| ParseError::new(s, "expected movements and nodes").in_day(8))?;
         let movements = movements
-            .chars()
-            .map(|c| match c {
-                'L' => 0,
-                'R' => 1,
-                _ => unreachable!("Unknown movement: Known are L, and R. Got: {c}"),
+            .char_indices()
+            .map(|(n, c)| match c {
+                'L' => Ok(0),
+                'R' => Ok(1),
+                _ => Err(ParseError::in_input(
+                    s,
+                    &movements[n..n + c.len_utf8()],
+                    "unknown movement, known are L and R",
+                )
+                .in_day(8)),
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
 
-        let spots = spots
+        let nodes = spots
             .lines()
             .map(|line| {
-                let key = &line[..3];
-                let left = &line[7..10];
-                let right = &line[12..15];
-                (key, [left, right])
+                let node = |range| {
+                    line.get(range).ok_or_else(|| {
+                        ParseError::in_input(s, line, "expected `AAA = (BBB, CCC)`").in_day(8)
+                    })
+                };
+                let key = node(0..3)?;
+                let left = node(7..10)?;
+                let right = node(12..15)?;
+                Ok((key, [left, right]))
             })
-            .collect();
+            .collect::<Result<Vec<_>, ParseError>>()?;
+        let spots: HashMap<_, _> = nodes.iter().copied().collect();
+        // Checked in input order so the first undefined node is reported
+        let mut next = nodes.iter().flat_map(|(_, next)| next);
+        if let Some(node) = next.find(|node| !spots.contains_key(*node)) {
+            let reason = format!("node `{node}` is never defined");
+            return Err(ParseError::in_input(s, node, reason).in_day(8));
+        }
 
         Ok(Self { movements, spots })
     }
@@ -64,22 +88,23 @@ impl<'a> Map<'a> {
     }
 }
 
-// https://github.com/gobanos/cargo-aoc/issues/20
-// #[aoc_generator(day8)]
-// fn parse<'a>(input: &'a str) -> Map<'a> {
-//     input.parse().unwrap()
-// }
+fn parse(input: &str) -> Result<Map<'_>, ParseError> {
+    Map::try_from(input)
+}
 
-#[aoc(day8, part1)]
-fn part1(input: &str) -> usize {
-    Map::try_from(input).unwrap().walk("AAA", "ZZZ")
+fn part1(map: &Map) -> usize {
+    map.walk("AAA", "ZZZ")
 }
 
-#[aoc(day8, part2)]
-fn part2(input: &str) -> usize {
-    Map::try_from(input).unwrap().ghost_walk("A", "Z")
+fn part2(map: &Map) -> usize {
+    map.ghost_walk("A", "Z")
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(8, 1, |input| timed(input, parse, part1)),
+    Solution::new(8, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,16 +146,31 @@ mod tests {
 
     #[test]
     fn part1_example_1() {
-        assert_eq!(part1(INPUT1), 2);
+        assert_eq!(part1(&parse(INPUT1).unwrap()), 2);
     }
 
     #[test]
     fn part1_example_2() {
-        assert_eq!(part1(INPUT2), 6);
+        assert_eq!(part1(&parse(INPUT2).unwrap()), 6);
     }
 
     #[test]
     fn part2_example_2() {
-        assert_eq!(part2(INPUT3), 6);
+        assert_eq!(part2(&parse(INPUT3).unwrap()), 6);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = Map::try_from("LXR\n\nAAA = (BBB, CCC)").err().unwrap();
+        assert_eq!((error.line, error.column), (Some(1), Some(2)));
+
+        let error = Map::try_from("LR\n\nAAA = (BBB, CCC)\nBBB = (")
+            .err()
+            .unwrap();
+        assert_eq!((error.line, error.column), (Some(4), Some(1)));
+
+        let error = Map::try_from("LR\n\nAAA = (BBB, CCC)").err().unwrap();
+        assert_eq!((error.line, error.day), (Some(3), Some(8)));
+        assert!(error.reason.ends_with("is never defined"));
     }
 }
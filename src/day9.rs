@@ -1,18 +1,10 @@
-use std::{num::ParseIntError, str::FromStr};
-
-use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
-fn str_numbers_to_collecatble<T, N>(s: &str) -> Result<T, ParseIntError>
-where
-    T: FromIterator<N>,
-    N: FromStr<Err = ParseIntError>,
-{
-    s.split(' ')
-        .filter(|n| !n.is_empty())
-        .map(N::from_str)
-        .collect()
-}
+use crate::{
+    error::{parse_lines, ParseError},
+    numbers,
+    runner::{timed, Solution},
+};
 
 fn find_next_number(numbers: &[i64]) -> i64 {
     let mut numbers = vec![numbers.to_vec()];
@@ -54,17 +46,14 @@ fn find_former_number(numbers: &[i64]) -> i64 {
     former_first
 }
 
-#[aoc_generator(day9)]
-fn parse(input: &str) -> Result<Vec<Vec<i64>>, ParseIntError> {
-    input.lines().map(str_numbers_to_collecatble).collect()
+fn parse(input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
+    parse_lines(9, input, |line| numbers::whitespace_separated(line, line))
 }
 
-#[aoc(day9, part1, first)]
 fn part1(numbers: &[Vec<i64>]) -> i64 {
     numbers.iter().map(|n| find_next_number(n.as_slice())).sum()
 }
 
-#[aoc(day9, part2, first)]
 fn part2(numbers: &[Vec<i64>]) -> i64 {
     numbers
         .iter()
@@ -72,6 +61,11 @@ fn part2(numbers: &[Vec<i64>]) -> i64 {
         .sum()
 }
 
+pub(crate) const SOLUTIONS: [Solution; 2] = [
+    Solution::new(9, 1, |input| timed(input, parse, part1)),
+    Solution::new(9, 2, |input| timed(input, parse, part2)),
+];
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -93,4 +87,10 @@ mod tests {
     fn part2_example() {
         assert_eq!(part2(&parse(INPUT).unwrap()), 2);
     }
+
+    #[test]
+    fn parse_error() {
+        let error = parse("0 3 6\n1 3 x 10").unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(5)));
+    }
 }
@@ -0,0 +1,252 @@
+use std::ops::Not;
+
+use crate::grid::Coord;
+
+use Direction::*;
+
+/// Signed `(x, y)` used when walking without grid bounds.
+pub type SignedCoord = (isize, isize);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [North, South, East, West];
+
+    pub fn turn_left(self) -> Self {
+        match self {
+            North => West,
+            West => South,
+            South => East,
+            East => North,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            North => East,
+            East => South,
+            South => West,
+            West => North,
+        }
+    }
+
+    /// New direction of a beam travelling in `self` after hitting `mirror`.
+    pub fn reflect(self, mirror: Mirror) -> Self {
+        match (mirror, self) {
+            (Mirror::Slash, North | South) => self.turn_right(),
+            (Mirror::Slash, East | West) => self.turn_left(),
+            (Mirror::Backslash, North | South) => self.turn_left(),
+            (Mirror::Backslash, East | West) => self.turn_right(),
+        }
+    }
+
+    pub fn offset(self) -> SignedCoord {
+        match self {
+            North => (0, -1),
+            South => (0, 1),
+            East => (1, 0),
+            West => (-1, 0),
+        }
+    }
+
+    /// One step from `coord`, or `None` when leaving a grid of `size`.
+    pub fn step(self, coord: Coord, size: Coord) -> Option<Coord> {
+        let (x, y) = coord;
+        Some(match self {
+            North => (x, y.checked_sub(1)?),
+            South => (x, y.checked_add(1).filter(|y| *y < size.1)?),
+            East => (x.checked_add(1).filter(|x| *x < size.0)?, y),
+            West => (x.checked_sub(1)?, y),
+        })
+    }
+
+    /// `len` steps from `coord` in an unbounded plane.
+    pub fn steps(self, coord: SignedCoord, len: isize) -> Option<SignedCoord> {
+        let (dx, dy) = self.offset();
+        Some((
+            coord.0.checked_add(dx.checked_mul(len)?)?,
+            coord.1.checked_add(dy.checked_mul(len)?)?,
+        ))
+    }
+}
+
+impl Not for Direction {
+    type Output = Direction;
+
+    fn not(self) -> Self::Output {
+        match self {
+            North => South,
+            South => North,
+            East => West,
+            West => East,
+        }
+    }
+}
+
+/// Accepts `UDLR`, `NSEW` and `^v<>`.
+impl TryFrom<char> for Direction {
+    type Error = char;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            'U' | 'N' | '^' => Ok(North),
+            'D' | 'S' | 'v' => Ok(South),
+            'R' | 'E' | '>' => Ok(East),
+            'L' | 'W' | '<' => Ok(West),
+            c => Err(c),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Mirror {
+    /// `/`
+    Slash,
+    /// `\`
+    Backslash,
+}
+
+impl TryFrom<char> for Mirror {
+    type Error = char;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '/' => Ok(Self::Slash),
+            '\\' => Ok(Self::Backslash),
+            c => Err(c),
+        }
+    }
+}
+
+/// The 8 directions including diagonals, clockwise from north.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Compass {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Compass {
+    pub const ALL: [Compass; 8] = [
+        Compass::North,
+        Compass::NorthEast,
+        Compass::East,
+        Compass::SouthEast,
+        Compass::South,
+        Compass::SouthWest,
+        Compass::West,
+        Compass::NorthWest,
+    ];
+
+    pub fn offset(self) -> SignedCoord {
+        match self {
+            Compass::North => (0, -1),
+            Compass::NorthEast => (1, -1),
+            Compass::East => (1, 0),
+            Compass::SouthEast => (1, 1),
+            Compass::South => (0, 1),
+            Compass::SouthWest => (-1, 1),
+            Compass::West => (-1, 0),
+            Compass::NorthWest => (-1, -1),
+        }
+    }
+
+    /// One step from `coord`, or `None` when leaving a grid of `size`.
+    pub fn step(self, coord: Coord, size: Coord) -> Option<Coord> {
+        let (dx, dy) = self.offset();
+        let x = coord.0.checked_add_signed(dx).filter(|x| *x < size.0)?;
+        let y = coord.1.checked_add_signed(dy).filter(|y| *y < size.1)?;
+        Some((x, y))
+    }
+}
+
+impl From<Direction> for Compass {
+    fn from(value: Direction) -> Self {
+        match value {
+            North => Compass::North,
+            South => Compass::South,
+            East => Compass::East,
+            West => Compass::West,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turns() {
+        for dir in Direction::ALL {
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.turn_left().turn_left(), !dir);
+            assert_eq!(dir.turn_right().turn_right(), !dir);
+        }
+        assert_eq!(North.turn_right(), East);
+        assert_eq!(North.turn_left(), West);
+    }
+
+    #[test]
+    fn test_reflect() {
+        assert_eq!(North.reflect(Mirror::Slash), East);
+        assert_eq!(South.reflect(Mirror::Slash), West);
+        assert_eq!(East.reflect(Mirror::Slash), North);
+        assert_eq!(West.reflect(Mirror::Slash), South);
+        assert_eq!(North.reflect(Mirror::Backslash), West);
+        assert_eq!(South.reflect(Mirror::Backslash), East);
+        assert_eq!(East.reflect(Mirror::Backslash), South);
+        assert_eq!(West.reflect(Mirror::Backslash), North);
+    }
+
+    #[test]
+    fn test_step() {
+        assert_eq!(North.step((0, 0), (3, 2)), None);
+        assert_eq!(West.step((0, 0), (3, 2)), None);
+        assert_eq!(East.step((1, 0), (3, 2)), Some((2, 0)));
+        assert_eq!(East.step((2, 0), (3, 2)), None);
+        assert_eq!(South.step((2, 0), (3, 2)), Some((2, 1)));
+        assert_eq!(South.step((2, 1), (3, 2)), None);
+    }
+
+    #[test]
+    fn test_steps() {
+        assert_eq!(North.steps((0, 0), 5), Some((0, -5)));
+        assert_eq!(East.steps((1, 1), 5), Some((6, 1)));
+        assert_eq!(West.steps((isize::MIN, 0), 1), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        for (chars, dir) in [("UN^", North), ("DSv", South), ("RE>", East), ("LW<", West)] {
+            for c in chars.chars() {
+                assert_eq!(Direction::try_from(c), Ok(dir));
+            }
+        }
+        assert_eq!(Direction::try_from('x'), Err('x'));
+    }
+
+    #[test]
+    fn test_compass_step() {
+        assert_eq!(Compass::NorthWest.step((0, 0), (2, 2)), None);
+        assert_eq!(Compass::SouthEast.step((0, 0), (2, 2)), Some((1, 1)));
+        assert_eq!(Compass::SouthEast.step((1, 1), (2, 2)), None);
+        assert_eq!(
+            Compass::ALL
+                .into_iter()
+                .filter_map(|c| c.step((1, 1), (3, 3)))
+                .count(),
+            8
+        );
+    }
+}
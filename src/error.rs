@@ -0,0 +1,220 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
+use nom::{Finish, IResult};
+
+/// Malformed puzzle input, pointing at the offending text.
+///
+/// Lines and columns are 1 based and counted in chars.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: Option<u32>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub text: String,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(text: &str, reason: impl Into<String>) -> Self {
+        Self {
+            day: None,
+            line: None,
+            column: None,
+            text: text.to_owned(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Error at `part`, which has to be a slice of `line`.
+    pub fn at(line: &str, part: &str, reason: impl Into<String>) -> Self {
+        let (_, column) = position(line, part);
+        Self {
+            column,
+            ..Self::new(part, reason)
+        }
+    }
+
+    /// Error at `part`, which has to be a slice of the (multi line) `input`.
+    pub fn in_input(input: &str, part: &str, reason: impl Into<String>) -> Self {
+        let (line, column) = position(input, part);
+        Self {
+            line,
+            column,
+            ..Self::new(part, reason)
+        }
+    }
+
+    pub fn in_day(mut self, day: u32) -> Self {
+        self.day.get_or_insert(day);
+        self
+    }
+
+    pub fn on_line(mut self, line: usize) -> Self {
+        self.line.get_or_insert(line);
+        self
+    }
+
+    /// Moves the error down `lines`, for errors found in a block of a larger
+    /// input.
+    pub fn offset_lines(mut self, lines: usize) -> Self {
+        self.line = Some(self.line.unwrap_or(1) + lines);
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {day}, ")?;
+        }
+        if let Some(line) = self.line {
+            write!(f, "line {line}, ")?;
+        }
+        if let Some(column) = self.column {
+            write!(f, "column {column}, ")?;
+        }
+        write!(f, "{}: {:?}", self.reason, self.text)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Line and column of `part` inside `source`, if `part` is a slice of it.
+fn position(source: &str, part: &str) -> (Option<usize>, Option<usize>) {
+    let start = source.as_ptr() as usize;
+    let part_start = part.as_ptr() as usize;
+    if part_start < start || part_start + part.len() > start + source.len() {
+        return (None, None);
+    }
+    let before = &source[..part_start - start];
+    let line_start = before.rfind('\n').map_or(0, |n| n + 1);
+    (
+        Some(before.matches('\n').count() + 1),
+        Some(before[line_start..].chars().count() + 1),
+    )
+}
+
+/// Number of lines in `input` before `block`, which has to be a slice of
+/// `input`.
+pub fn lines_before(input: &str, block: &str) -> usize {
+    position(input, block).0.map_or(0, |line| line - 1)
+}
+
+/// Parses `part` of `line` with [`FromStr`], pointing at `part` on failure.
+pub fn parse_field<T>(line: &str, part: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    part.trim()
+        .parse()
+        .map_err(|e| ParseError::at(line, part, format!("{e}")))
+}
+
+/// Finishes a nom parse of `input`, requiring that only whitespace is left.
+pub fn finish_nom<'a, T>(input: &'a str, result: IResult<&'a str, T>) -> Result<T, ParseError> {
+    let (rest, value) = result.finish().map_err(|e| {
+        ParseError::in_input(
+            input,
+            e.input.lines().next().unwrap_or(e.input),
+            format!("unexpected input ({})", e.code.description()),
+        )
+    })?;
+    let rest = rest.trim_start();
+    if rest.is_empty() {
+        Ok(value)
+    } else {
+        Err(ParseError::in_input(
+            input,
+            rest.lines().next().unwrap_or(rest),
+            "unexpected trailing input",
+        ))
+    }
+}
+
+/// Runs `f` on every line, adding the line number and `day` to any error.
+pub fn parse_lines<T, F>(day: u32, input: &str, mut f: F) -> Result<Vec<T>, ParseError>
+where
+    F: FnMut(&str) -> Result<T, ParseError>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(n, line)| f(line).map_err(|e| e.on_line(n + 1).in_day(day)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use nom::{bytes::complete::tag, character::complete};
+
+    use super::*;
+
+    #[test]
+    fn test_at() {
+        let line = "Game 1: 3 bleu";
+        let error = ParseError::at(line, &line[10..], "unknown color");
+        assert_eq!(error.column, Some(11));
+        assert_eq!(error.text, "bleu");
+        assert_eq!(error.line, None);
+    }
+
+    #[test]
+    fn test_in_input() {
+        let input = "abc\ndéf\nghi";
+        let error = ParseError::in_input(input, &input[7..8], "bad");
+        assert_eq!((error.line, error.column), (Some(2), Some(3)));
+    }
+
+    #[test]
+    fn test_not_a_slice() {
+        let other = String::from("abc");
+        let error = ParseError::at("abc", &other, "bad");
+        assert_eq!((error.line, error.column), (None, None));
+
+        let input = "abc\ndef";
+        let (line, rest) = input.split_at(4);
+        let error = ParseError::in_input(line, rest, "bad");
+        assert_eq!((error.line, error.column), (None, None));
+        let error = ParseError::in_input(&input[..5], &input[4..], "bad");
+        assert_eq!((error.line, error.column), (None, None));
+    }
+
+    #[test]
+    fn test_display() {
+        let line = "a b";
+        let error = ParseError::at(line, &line[2..], "not a number")
+            .on_line(4)
+            .in_day(6);
+        assert_eq!(
+            error.to_string(),
+            r#"day 6, line 4, column 3, not a number: "b""#
+        );
+    }
+
+    #[test]
+    fn test_finish_nom() {
+        let input = "1\n2\nx";
+        let numbers = |i| nom::multi::separated_list1(tag("\n"), complete::u32)(i);
+        let error = finish_nom(input, numbers(input)).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(3), Some(1)));
+        assert_eq!(error.text, "x");
+
+        let input = "1\n2\n";
+        assert_eq!(finish_nom(input, numbers(input)), Ok(vec![1, 2]));
+
+        let input = "x";
+        let error = finish_nom(input, numbers(input)).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(1), Some(1)));
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let parsed = parse_lines(9, "1\n2\nx", |line| parse_field::<u32>(line, line));
+        let error = parsed.unwrap_err();
+        assert_eq!(
+            (error.day, error.line, error.column),
+            (Some(9), Some(3), Some(1))
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+/// How a report lays out its rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Aligned columns for reading, see [`table`].
+    Table,
+    /// An array of objects keyed by the header, see [`json`].
+    Json,
+    /// Comma separated, see [`csv`].
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown format {s:?}, expected table, json or csv")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// `rows` in `format`, the first row is the header. `align` is only used by
+/// [`Format::Table`].
+pub fn render(rows: &[Vec<String>], format: Format, align: impl Fn(usize) -> Align) -> String {
+    match format {
+        Format::Table => table(rows, align),
+        Format::Json => json(rows),
+        Format::Csv => csv(rows),
+    }
+}
+
+/// Pads the columns of `rows` to the same width, two spaces apart, with
+/// `align` picking the side of every column. Widths count chars.
+pub fn table(rows: &[Vec<String>], align: impl Fn(usize) -> Align) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths = (0..columns)
+        .map(|n| {
+            rows.iter()
+                .filter_map(|row| row.get(n))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect_vec();
+    rows.iter()
+        .map(|row| {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .enumerate()
+                .map(|(n, (cell, &width))| match align(n) {
+                    Align::Left => format!("{cell:<width$}"),
+                    Align::Right => format!("{cell:>width$}"),
+                });
+            line.format("  ").to_string().trim_end().to_owned()
+        })
+        .join("\n")
+}
+
+/// `rows` as CSV, quoting the cells that need it.
+pub fn csv(rows: &[Vec<String>]) -> String {
+    let cell = |cell: &String| {
+        if cell.contains([',', '"', '\n']) {
+            format!("\"{}\"", cell.replace('"', "\"\""))
+        } else {
+            cell.clone()
+        }
+    };
+    rows.iter()
+        .map(|row| row.iter().map(cell).join(","))
+        .join("\n")
+}
+
+/// The rows after the header as an array of objects, one per line. Integers
+/// and booleans are written as they are, every other cell as a string.
+pub fn json(rows: &[Vec<String>]) -> String {
+    let Some((header, rows)) = rows.split_first().filter(|(_, rows)| !rows.is_empty()) else {
+        return "[]".to_owned();
+    };
+    let objects = rows.iter().map(|row| {
+        let fields = header.iter().zip(row).map(|(name, value)| {
+            format!("{}: {}", json_value(name, false), json_value(value, true))
+        });
+        format!("  {{{}}}", fields.format(", "))
+    });
+    format!("[\n{}\n]", objects.format(",\n"))
+}
+
+fn json_value(value: &str, bare: bool) -> String {
+    if bare && (value.parse::<i128>().is_ok() || value == "true" || value == "false") {
+        return value.to_owned();
+    }
+    let mut quoted = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            c if c.is_control() => quoted += &format!("\\u{:04x}", c as u32),
+            c => quoted.push(c),
+        }
+    }
+    quoted + "\""
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<Vec<String>> {
+        [["name", "n"], ["a, \"b\"", "12"], ["ü", "-3"]]
+            .iter()
+            .map(|row| row.map(str::to_owned).to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn test_table() {
+        let align = |n| if n == 0 { Align::Left } else { Align::Right };
+        assert_eq!(
+            table(&rows(), align),
+            "name     n\na, \"b\"  12\nü       -3"
+        );
+        assert_eq!(
+            table(&rows(), |_| Align::Right).lines().last(),
+            Some("     ü  -3")
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(csv(&rows()), "name,n\n\"a, \"\"b\"\"\",12\nü,-3");
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            json(&rows()),
+            "[\n  {\"name\": \"a, \\\"b\\\"\", \"n\": 12},\n  {\"name\": \"ü\", \"n\": -3}\n]"
+        );
+        assert_eq!(json(&rows()[..1]), "[]");
+        assert_eq!("json".parse(), Ok(Format::Json));
+    }
+}
@@ -0,0 +1,290 @@
+use std::{
+    fmt::{Debug, Display},
+    ops::{Index, IndexMut},
+};
+
+use itertools::Itertools;
+
+use crate::{
+    direction::{Compass, Direction},
+    error::ParseError,
+};
+
+/// `(x, y)` with `(0, 0)` in the top left corner.
+pub type Coord = (usize, usize);
+
+/// Heap backed row major grid, sized when it is parsed.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Parses one cell per char, one row per line. Every line must have the
+    /// same length.
+    pub fn parse_with<E, F>(input: &str, mut f: F) -> Result<Self, ParseError>
+    where
+        F: FnMut(char) -> Result<T, E>,
+        E: Display,
+    {
+        let mut cells = Vec::with_capacity(input.len());
+        let mut width = None;
+        let mut height = 0;
+        for (y, line) in input.lines().enumerate() {
+            let len = line.chars().count();
+            let expected = *width.get_or_insert(len);
+            if expected != len {
+                return Err(ParseError::at(
+                    line,
+                    line,
+                    format!("expected a row of {expected} cells, found {len}"),
+                )
+                .on_line(y + 1));
+            }
+            for (x, c) in line.char_indices() {
+                cells.push(f(c).map_err(|e| {
+                    ParseError::at(
+                        line,
+                        &line[x..x + c.len_utf8()],
+                        format!("unknown cell {e}"),
+                    )
+                    .on_line(y + 1)
+                })?);
+            }
+            height += 1;
+        }
+        Ok(Self {
+            cells,
+            width: width.unwrap_or(0),
+            height,
+        })
+    }
+
+    /// A grid of `cells` in row major order, `width` cells per row.
+    pub fn from_cells(width: usize, cells: Vec<T>) -> Self {
+        assert!(
+            cells.len().is_multiple_of(width),
+            "{} cells don't fill rows of {width}",
+            cells.len()
+        );
+        Self {
+            height: cells.len().checked_div(width).unwrap_or(0),
+            cells,
+            width,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn size(&self) -> Coord {
+        (self.width, self.height)
+    }
+
+    pub fn contains(&self, coord: Coord) -> bool {
+        coord.0 < self.width && coord.1 < self.height
+    }
+
+    pub fn get(&self, coord: Coord) -> Option<&T> {
+        self.contains(coord).then(|| &self[coord])
+    }
+
+    pub fn get_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        if self.contains(coord) {
+            Some(&mut self[coord])
+        } else {
+            None
+        }
+    }
+
+    /// Every coordinate, row by row.
+    pub fn coords(&self) -> impl Iterator<Item = Coord> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    pub fn indexed_iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.coords().zip(self.cells.iter())
+    }
+
+    pub fn position<P: FnMut(&T) -> bool>(&self, predicate: P) -> Option<Coord> {
+        self.cells
+            .iter()
+            .position(predicate)
+            .map(|i| (i % self.width, i / self.width))
+    }
+
+    /// The up to 4 coordinates north, south, east and west of `coord` that
+    /// are inside the grid.
+    pub fn neighbours(&self, coord: Coord) -> impl Iterator<Item = Coord> {
+        let size = self.size();
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |dir| dir.step(coord, size))
+    }
+
+    /// Like [`Grid::neighbours`] but including the diagonals.
+    pub fn adjacent(&self, coord: Coord) -> impl Iterator<Item = Coord> {
+        let size = self.size();
+        Compass::ALL
+            .into_iter()
+            .filter_map(move |dir| dir.step(coord, size))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column {x} out of bounds");
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Swaps rows and columns.
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: self.columns().flatten().cloned().collect(),
+            width: self.height,
+            height: self.width,
+        }
+    }
+}
+
+impl<T> Index<Coord> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: Coord) -> &Self::Output {
+        assert!(self.contains(index), "{index:?} is outside the grid");
+        &self.cells[index.1 * self.width + index.0]
+    }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+    fn index_mut(&mut self, index: Coord) -> &mut Self::Output {
+        assert!(self.contains(index), "{index:?} is outside the grid");
+        &mut self.cells[index.1 * self.width + index.0]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.rows().map(|line| line.iter().join("")).join("\n"))
+    }
+}
+
+impl<T: Display> Debug for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "\n{}", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    static INPUT: &str = indoc! {"
+        123
+        456
+    "};
+
+    fn digits(input: &str) -> Grid<u32> {
+        Grid::parse_with(input, |c| c.to_digit(10).ok_or(c)).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits(INPUT);
+        assert_eq!(grid.size(), (3, 2));
+        assert_eq!(grid[(0, 0)], 1);
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.to_string(), INPUT.trim_end());
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = Grid::parse_with("12\n3x", |c| c.to_digit(10).ok_or(c)).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(2)));
+        assert_eq!(error.text, "x");
+
+        let error = Grid::parse_with("12\n345", |c| c.to_digit(10).ok_or(c)).unwrap_err();
+        assert_eq!((error.line, error.column), (Some(2), Some(1)));
+    }
+
+    #[test]
+    fn test_from_cells() {
+        assert_eq!(Grid::from_cells(3, vec![1, 2, 3, 4, 5, 6]), digits(INPUT));
+        assert_eq!(Grid::<u32>::from_cells(0, Vec::new()).size(), (0, 0));
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = digits(INPUT);
+        assert_eq!(grid.row(1), &[4, 5, 6]);
+        assert_eq!(grid.column(1).copied().collect_vec(), vec![2, 5]);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.columns().count(), 3);
+    }
+
+    #[test]
+    fn test_transpose() {
+        let grid = digits(INPUT).transpose();
+        assert_eq!(grid.size(), (2, 3));
+        assert_eq!(grid.to_string(), "14\n25\n36");
+        assert_eq!(grid.transpose(), digits(INPUT));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = digits(INPUT);
+        assert_eq!(grid.neighbours((0, 0)).collect_vec(), vec![(0, 1), (1, 0)]);
+        assert_eq!(
+            grid.neighbours((1, 1)).collect_vec(),
+            vec![(1, 0), (2, 1), (0, 1)]
+        );
+        assert_eq!(grid.adjacent((0, 0)).count(), 3);
+        assert_eq!(grid.adjacent((1, 0)).count(), 5);
+    }
+
+    #[test]
+    fn test_position() {
+        let grid = digits(INPUT);
+        assert_eq!(grid.position(|n| *n == 5), Some((1, 1)));
+        assert_eq!(grid.position(|n| *n == 7), None);
+    }
+}
@@ -1,28 +1,57 @@
-#![feature(pattern, assert_matches)]
+#![cfg_attr(feature = "nightly", feature(pattern))]
 
-mod day1;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod day8;
-mod day9;
+/// Stable stand-in for `std::assert_matches::assert_matches`.
+macro_rules! assert_matches {
+    ($left:expr, $($pattern:pat_param)|+ $(if $guard:expr)? $(,)?) => {
+        match $left {
+            $($pattern)|+ $(if $guard)? => {}
+            ref left => panic!(
+                "assertion `left matches right` failed\n  left: {:?}\n right: {}",
+                left,
+                stringify!($($pattern)|+ $(if $guard)?)
+            ),
+        }
+    };
+    ($left:expr, $($pattern:pat_param)|+ $(if $guard:expr)?, $($arg:tt)+) => {
+        match $left {
+            $($pattern)|+ $(if $guard)? => {}
+            ref left => panic!(
+                "assertion `left matches right` failed: {}\n  left: {:?}\n right: {}",
+                format_args!($($arg)+),
+                left,
+                stringify!($($pattern)|+ $(if $guard)?)
+            ),
+        }
+    };
+}
 
-extern crate aoc_runner;
-
-#[macro_use]
-extern crate aoc_runner_derive;
-
-aoc_lib! { year = 2023 }
+pub mod day1;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod bench;
+pub mod direction;
+pub mod error;
+pub mod format;
+pub mod grid;
+pub mod numbers;
+pub mod runner;
+pub mod separator;
+#[cfg(test)]
+mod strategies;
+pub mod verify;
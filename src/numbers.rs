@@ -0,0 +1,141 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    error::{parse_field, ParseError},
+    separator::Separator,
+};
+
+/// Numbers in `s`, a slice of `line`, separated by `separator`. Fields are
+/// trimmed and empty ones skipped, so runs of separators are fine.
+///
+/// Like [`parse_field`], errors point at the offending number in `line`.
+pub fn separated<T, N, P>(line: &str, s: &str, separator: P) -> Result<T, ParseError>
+where
+    T: FromIterator<N>,
+    N: FromStr,
+    N::Err: Display,
+    P: Separator,
+{
+    separator
+        .separate(s)
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(|n| parse_field(line, n))
+        .collect()
+}
+
+/// Numbers separated by any amount of whitespace.
+pub fn whitespace_separated<T, N>(line: &str, s: &str) -> Result<T, ParseError>
+where
+    T: FromIterator<N>,
+    N: FromStr,
+    N::Err: Display,
+{
+    s.split_whitespace().map(|n| parse_field(line, n)).collect()
+}
+
+/// Numbers separated by `,` with optional whitespace around them.
+pub fn comma_separated<T, N>(line: &str, s: &str) -> Result<T, ParseError>
+where
+    T: FromIterator<N>,
+    N: FromStr,
+    N::Err: Display,
+{
+    separated(line, s, ',')
+}
+
+/// Splits `<label>: <values>` into the trimmed label and the values.
+pub fn split_label(line: &str) -> Result<(&str, &str), ParseError> {
+    line.split_once(':')
+        .map(|(label, values)| (label.trim(), values))
+        .ok_or_else(|| ParseError::at(line, line, "expected `<label>: <numbers>`"))
+}
+
+/// Whitespace separated numbers after a label, like `Time:  7 15 30`.
+pub fn labelled<T, N>(line: &str) -> Result<(&str, T), ParseError>
+where
+    T: FromIterator<N>,
+    N: FromStr,
+    N::Err: Display,
+{
+    let (label, values) = split_label(line)?;
+    Ok((label, whitespace_separated(line, values)?))
+}
+
+/// A single number with bad kerning, `7  15   30` is `71530`.
+pub fn bad_kerning<N>(line: &str, s: &str) -> Result<N, ParseError>
+where
+    N: FromStr,
+    N::Err: Display,
+{
+    let s = s.trim();
+    s.split_whitespace()
+        .collect::<String>()
+        .parse()
+        .map_err(|e| {
+            let part = s
+                .split_whitespace()
+                .find(|n| n.parse::<N>().is_err())
+                .unwrap_or(s);
+            ParseError::at(line, part, format!("{e}"))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_separated() {
+        let line = "1,2,,3";
+        assert_eq!(separated(line, line, ','), Ok(vec![1, 2, 3]));
+        let line = "1;2 3";
+        assert_eq!(separated(line, line, [';', ' ']), Ok(vec![1u8, 2, 3]));
+        let line = "3, 1, 3";
+        assert_eq!(comma_separated(line, line), Ok(HashSet::from([1, 3])));
+    }
+
+    #[test]
+    fn test_whitespace_separated() {
+        let line = "  0 -3\t6  ";
+        assert_eq!(whitespace_separated(line, line), Ok(vec![0i64, -3, 6]));
+        assert_eq!(whitespace_separated(line, ""), Ok(Vec::<i64>::new()));
+    }
+
+    #[test]
+    fn test_labelled() {
+        let line = "Time:      7  15   30";
+        assert_eq!(labelled(line), Ok(("Time", vec![7, 15, 30])));
+        let (_, values) = split_label(line).unwrap();
+        assert_eq!(bad_kerning(line, values), Ok(71530));
+    }
+
+    #[test]
+    fn test_errors() {
+        let line = "Card 1: 41 4x 83";
+        let error = labelled::<Vec<u32>, u32>(line).unwrap_err();
+        assert_eq!((error.column, error.text.as_str()), (Some(12), "4x"));
+
+        let line = "1, -2";
+        let error = comma_separated::<Vec<u32>, u32>(line, line).unwrap_err();
+        assert_eq!((error.column, error.text.as_str()), (Some(4), "-2"));
+
+        let error = labelled::<Vec<u32>, u32>("7 15 30").unwrap_err();
+        assert_eq!(error.column, Some(1));
+
+        let line = "Time: 7 1x 30";
+        let (_, values) = split_label(line).unwrap();
+        let error = bad_kerning::<u32>(line, values).unwrap_err();
+        assert_eq!((error.column, error.text.as_str()), (Some(9), "1x"));
+
+        let line = "Time: 99999 99999";
+        let (_, values) = split_label(line).unwrap();
+        let error = bad_kerning::<u32>(line, values).unwrap_err();
+        assert_eq!(
+            (error.column, error.text.as_str()),
+            (Some(7), "99999 99999")
+        );
+    }
+}
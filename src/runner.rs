@@ -0,0 +1,133 @@
+use std::{
+    borrow::Borrow,
+    fmt::Display,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    day1, day10, day11, day12, day13, day14, day15, day16, day17, day18, day19, day2, day3, day4,
+    day5, day6, day7, day8, day9, error::ParseError,
+};
+
+/// A single registered part of a day.
+#[derive(Clone, Copy)]
+pub struct Solution {
+    pub day: u32,
+    pub part: u32,
+    run: fn(&str) -> Result<Run, ParseError>,
+}
+
+/// Answer of a [`Solution`] with the time spent in the generator and the
+/// solver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Run {
+    pub answer: String,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+}
+
+impl Solution {
+    pub const fn new(day: u32, part: u32, run: fn(&str) -> Result<Run, ParseError>) -> Self {
+        Self { day, part, run }
+    }
+
+    /// Runs the solution, trimming trailing newlines from `input` like
+    /// `cargo aoc` does.
+    pub fn run(&self, input: &str) -> Result<Run, ParseError> {
+        (self.run)(input.trim_end_matches('\n'))
+    }
+}
+
+/// Parses `input` and solves it, timing both steps.
+///
+/// The generator output may borrow from `input`.
+pub fn timed<'a, T, B, A, P, S>(input: &'a str, parse: P, solve: S) -> Result<Run, ParseError>
+where
+    P: FnOnce(&'a str) -> Result<T, ParseError>,
+    T: Borrow<B>,
+    B: ?Sized,
+    S: FnOnce(&B) -> A,
+    A: Display,
+{
+    let start = Instant::now();
+    let parsed = parse(input)?;
+    let parse_time = start.elapsed();
+    let start = Instant::now();
+    let answer = solve(parsed.borrow()).to_string();
+    let solve_time = start.elapsed();
+    Ok(Run {
+        answer,
+        parse_time,
+        solve_time,
+    })
+}
+
+/// Every registered solution, ordered by day and part.
+pub fn solutions() -> impl Iterator<Item = Solution> {
+    [
+        &day1::SOLUTIONS[..],
+        &day2::SOLUTIONS,
+        &day3::SOLUTIONS,
+        &day4::SOLUTIONS,
+        &day5::SOLUTIONS,
+        &day6::SOLUTIONS,
+        &day7::SOLUTIONS,
+        &day8::SOLUTIONS,
+        &day9::SOLUTIONS,
+        &day10::SOLUTIONS,
+        &day11::SOLUTIONS,
+        &day12::SOLUTIONS,
+        &day13::SOLUTIONS,
+        &day14::SOLUTIONS,
+        &day15::SOLUTIONS,
+        &day16::SOLUTIONS,
+        &day17::SOLUTIONS,
+        &day18::SOLUTIONS,
+        &day19::SOLUTIONS,
+    ]
+    .into_iter()
+    .flatten()
+    .copied()
+}
+
+pub fn find(day: u32, part: u32) -> Option<Solution> {
+    solutions().find(|s| s.day == day && s.part == part)
+}
+
+/// The input of `day`, where `cargo aoc input` would download it to.
+pub fn input_path(day: u32) -> PathBuf {
+    PathBuf::from(format!("input/2023/day{day}.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn test_registry_is_complete() {
+        let registered = solutions().map(|s| (s.day, s.part)).collect_vec();
+        let expected = (1..=19).cartesian_product(1..=2).collect_vec();
+        assert_eq!(registered, expected);
+    }
+
+    #[test]
+    fn test_run() {
+        let run = find(9, 1).unwrap().run("0 3 6 9 12 15\n\n").unwrap();
+        assert_eq!(run.answer, "18");
+    }
+
+    #[test]
+    fn test_run_borrowed_generator() {
+        let input = "LLR\n\nAAA = (BBB, BBB)\nBBB = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)\n";
+        assert_eq!(find(8, 1).unwrap().run(input).unwrap().answer, "6");
+    }
+
+    #[test]
+    fn test_run_parse_error() {
+        let error = find(9, 1).unwrap().run("0 3 x").unwrap_err();
+        assert_eq!((error.day, error.column), (Some(9), Some(5)));
+    }
+}
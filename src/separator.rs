@@ -0,0 +1,60 @@
+#[cfg(feature = "nightly")]
+use std::str::pattern::Pattern;
+
+/// Something to split a `str` on, a stable stand-in for
+/// [`std::str::pattern::Pattern`].
+///
+/// With the `nightly` feature every `Pattern` is a separator.
+pub trait Separator {
+    fn separate<'a>(&self, s: &'a str) -> impl Iterator<Item = &'a str>;
+}
+
+#[cfg(feature = "nightly")]
+impl<P: Pattern + Clone> Separator for P {
+    fn separate<'a>(&self, s: &'a str) -> impl Iterator<Item = &'a str> {
+        s.split(self.clone())
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+macro_rules! separator_impls {
+    ($([$($param:tt)*] $ty:ty),* $(,)?) => {
+        $(
+            impl<$($param)*> Separator for $ty {
+                fn separate<'a>(&self, s: &'a str) -> impl Iterator<Item = &'a str> {
+                    s.split(*self)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(not(feature = "nightly"))]
+separator_impls! {
+    [] char,
+    ['b] &'b str,
+    ['b] &'b [char],
+    [const N: usize] [char; N],
+    ['b, const N: usize] &'b [char; N],
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn test_separate() {
+        assert_eq!(
+            ','.separate("1,2,,3").collect_vec(),
+            vec!["1", "2", "", "3"]
+        );
+        assert_eq!(", ".separate("1, 2,3").collect_vec(), vec!["1", "2,3"]);
+        assert_eq!(
+            [',', ' '].separate("1, 2").collect_vec(),
+            vec!["1", "", "2"]
+        );
+        assert_eq!((&[';'][..]).separate("1;2").collect_vec(), vec!["1", "2"]);
+    }
+}
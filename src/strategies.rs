@@ -0,0 +1,100 @@
+use itertools::Itertools;
+use proptest::prelude::*;
+
+use crate::direction::{
+    Direction::{self, *},
+    SignedCoord,
+};
+
+/// A closed walk of `(direction, length)` moves around a polygon that never
+/// touches itself, like the loop of day10 or the dig plan of day18.
+///
+/// The polygon is a histogram of plateaus of random width and height, rotated
+/// and possibly walked backwards.
+pub fn rectilinear_loop() -> impl Strategy<Value = Vec<(Direction, usize)>> {
+    let plateaus = prop::collection::vec((1..4_usize, 1..6_usize), 1..6);
+    (plateaus, 0..4_usize, any::<bool>()).prop_map(|(plateaus, turns, backwards)| {
+        let (first_width, first_height) = plateaus[0];
+        let (_, last_height) = plateaus[plateaus.len() - 1];
+        let mut moves = vec![
+            (East, plateaus.iter().map(|p| p.0).sum()),
+            (North, last_height),
+        ];
+        for (&(width, height), &(_, left_height)) in plateaus.iter().rev().tuple_windows() {
+            moves.push((West, width));
+            if left_height > height {
+                moves.push((North, left_height - height));
+            } else if left_height < height {
+                moves.push((South, height - left_height));
+            }
+        }
+        moves.extend([(West, first_width), (South, first_height)]);
+
+        let moves = moves
+            .into_iter()
+            .coalesce(|a, b| {
+                if a.0 == b.0 {
+                    Ok((a.0, a.1 + b.1))
+                } else {
+                    Err((a, b))
+                }
+            })
+            .map(|(dir, len)| ((0..turns).fold(dir, |d, _| d.turn_right()), len))
+            .collect_vec();
+        if backwards {
+            moves
+                .into_iter()
+                .rev()
+                .map(|(dir, len)| (!dir, len))
+                .collect()
+        } else {
+            moves
+        }
+    })
+}
+
+/// Every cell of the loop walked by `moves` from `(0, 0)`, in order and
+/// without returning to the start.
+pub fn loop_cells(moves: &[(Direction, usize)]) -> Vec<SignedCoord> {
+    let mut cells = vec![(0, 0)];
+    for &(dir, len) in moves {
+        for _ in 0..len {
+            let last = *cells.last().unwrap();
+            cells.push(dir.steps(last, 1).unwrap());
+        }
+    }
+    assert_eq!(cells.pop(), Some((0, 0)), "the moves don't form a loop");
+    cells
+}
+
+/// Number of cells strictly inside the loop, by Pick's theorem.
+pub fn interior(cells: &[SignedCoord]) -> usize {
+    let twice_area = cells
+        .iter()
+        .circular_tuple_windows()
+        .map(|(a, b)| a.0 * b.1 - a.1 * b.0)
+        .sum::<isize>()
+        .unsigned_abs();
+    (twice_area + 2 - cells.len()) / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loop() {
+        let moves = [(East, 2), (South, 2), (West, 2), (North, 2)];
+        let cells = loop_cells(&moves);
+        assert_eq!(cells.len(), 8);
+        assert_eq!(interior(&cells), 1);
+    }
+
+    proptest! {
+        #[test]
+        fn loops_are_simple(moves in rectilinear_loop()) {
+            let cells = loop_cells(&moves);
+            prop_assert!(cells.iter().all_unique());
+        }
+    }
+}
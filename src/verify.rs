@@ -0,0 +1,271 @@
+use std::{collections::HashMap, fmt::Display, io, str::FromStr, time::Duration};
+
+use itertools::Itertools;
+use toml::{Table, Value};
+
+use crate::{
+    error::ParseError,
+    format::{table, Align},
+    runner,
+};
+
+/// Known answers, read from an `answers.toml` like
+///
+/// ```toml
+/// [day1]
+/// part1 = 142
+/// part2 = "281"
+///
+/// # overrides for a specific input, see `input_hash`
+/// [day1.inputs.af63bd4c8601b7be]
+/// part1 = 209
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers(HashMap<(u32, u32, Option<String>), String>);
+
+impl Answers {
+    /// The answer for `input_hash` if there is one, else the one for the day.
+    pub fn get(&self, day: u32, part: u32, input_hash: &str) -> Option<&str> {
+        self.0
+            .get(&(day, part, Some(input_hash.to_owned())))
+            .or_else(|| self.0.get(&(day, part, None)))
+            .map(String::as_str)
+    }
+
+    fn insert_parts(
+        &mut self,
+        day: u32,
+        hash: Option<&str>,
+        table: &Table,
+    ) -> Result<(), ParseError> {
+        for (key, value) in table {
+            if hash.is_none() && key == "inputs" {
+                let inputs = value
+                    .as_table()
+                    .ok_or_else(|| ParseError::new(key, "expected a table of input hashes"))?;
+                for (hash, parts) in inputs {
+                    let parts = parts
+                        .as_table()
+                        .ok_or_else(|| ParseError::new(hash, "expected a table of parts"))?;
+                    self.insert_parts(day, Some(hash), parts)?;
+                }
+                continue;
+            }
+            let part = numbered(key, "part")?;
+            let answer = match value {
+                Value::String(s) => s.clone(),
+                Value::Integer(n) => n.to_string(),
+                _ => return Err(ParseError::new(key, "expected a string or integer answer")),
+            };
+            self.0.insert((day, part, hash.map(str::to_owned)), answer);
+        }
+        Ok(())
+    }
+}
+
+/// `N` of a `{prefix}N` key.
+fn numbered(key: &str, prefix: &str) -> Result<u32, ParseError> {
+    key.strip_prefix(prefix)
+        .and_then(|n| n.parse().ok())
+        .ok_or_else(|| ParseError::new(key, format!("expected {prefix}<N>")))
+}
+
+impl FromStr for Answers {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let table: Table = s
+            .parse()
+            .map_err(|e: toml::de::Error| ParseError::new(s, e.message()))?;
+        let mut answers = Answers::default();
+        for (key, value) in &table {
+            let day = numbered(key, "day")?;
+            let parts = value
+                .as_table()
+                .ok_or_else(|| ParseError::new(key, "expected a table of parts"))?;
+            answers.insert_parts(day, None, parts)?;
+        }
+        Ok(answers)
+    }
+}
+
+/// FNV-1a hash of the input, ignoring trailing whitespace.
+pub fn input_hash(input: &str) -> String {
+    let hash = input
+        .trim_end()
+        .bytes()
+        .fold(0xcbf29ce484222325_u64, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{hash:016x}")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail {
+        expected: String,
+    },
+    /// No stored answer to compare with.
+    Missing,
+    /// The input couldn't be read, the solution wasn't run.
+    NoInput(String),
+    Error(ParseError),
+}
+
+impl Status {
+    /// Whether this should fail the verification.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Status::Fail { .. } | Status::Error(_))
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Pass => write!(f, "pass"),
+            Status::Fail { expected } => write!(f, "FAIL (expected {expected})"),
+            Status::Missing => write!(f, "missing"),
+            Status::NoInput(e) => write!(f, "no input ({e})"),
+            Status::Error(e) => write!(f, "ERROR ({e})"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub day: u32,
+    pub part: u32,
+    pub answer: Option<String>,
+    pub time: Duration,
+    pub status: Status,
+}
+
+/// Runs every registered solution on the input returned by `read_input` for
+/// its day and compares the result with `answers`.
+pub fn verify<F>(answers: &Answers, mut read_input: F) -> Vec<Check>
+where
+    F: FnMut(u32) -> io::Result<String>,
+{
+    let mut inputs = HashMap::new();
+    runner::solutions()
+        .map(|solution| {
+            let (day, part) = (solution.day, solution.part);
+            let check = |answer, time, status| Check {
+                day,
+                part,
+                answer,
+                time,
+                status,
+            };
+            let input = match inputs.entry(day).or_insert_with(|| read_input(day)) {
+                Ok(input) => input,
+                Err(e) => return check(None, Duration::ZERO, Status::NoInput(e.to_string())),
+            };
+            match solution.run(input) {
+                Ok(run) => {
+                    let time = run.parse_time + run.solve_time;
+                    let status = match answers.get(day, part, &input_hash(input)) {
+                        Some(expected) if expected == run.answer => Status::Pass,
+                        Some(expected) => Status::Fail {
+                            expected: expected.to_owned(),
+                        },
+                        None => Status::Missing,
+                    };
+                    check(Some(run.answer), time, status)
+                }
+                Err(e) => check(None, Duration::ZERO, Status::Error(e)),
+            }
+        })
+        .collect()
+}
+
+/// One line per check, with aligned columns.
+pub fn report(checks: &[Check]) -> String {
+    let header = ["day", "part", "answer", "time", "status"].map(str::to_owned);
+    let rows = checks.iter().map(|c| {
+        [
+            c.day.to_string(),
+            c.part.to_string(),
+            c.answer.clone().unwrap_or_else(|| "-".to_owned()),
+            format!("{:?}", c.time),
+            c.status.to_string(),
+        ]
+    });
+    let rows = [header]
+        .into_iter()
+        .chain(rows)
+        .map(Vec::from)
+        .collect_vec();
+    table(&rows, |column| match column {
+        2 | 4 => Align::Left,
+        _ => Align::Right,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    static ANSWERS: &str = indoc! {r#"
+        [day9]
+        part1 = 114
+        part2 = "3"
+
+        [day9.inputs.0123456789abcdef]
+        part2 = "2"
+    "#};
+
+    static DAY9: &str = indoc! {"
+        0 3 6 9 12 15
+        1 3 6 10 15 21
+        10 13 16 21 30 45
+    "};
+
+    #[test]
+    fn test_parse() {
+        let answers: Answers = ANSWERS.parse().unwrap();
+        assert_eq!(answers.get(9, 1, "0123456789abcdef"), Some("114"));
+        assert_eq!(answers.get(9, 2, "0123456789abcdef"), Some("2"));
+        assert_eq!(answers.get(9, 2, "fedcba9876543210"), Some("3"));
+        assert_eq!(answers.get(1, 1, "0123456789abcdef"), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = "[day9]\npart1 = [1]".parse::<Answers>().unwrap_err();
+        assert_eq!(error.text, "part1");
+        let error = "[nine]\npart1 = 1".parse::<Answers>().unwrap_err();
+        assert_eq!(error.text, "nine");
+    }
+
+    #[test]
+    fn test_input_hash() {
+        assert_eq!(input_hash(""), "cbf29ce484222325");
+        assert_eq!(input_hash("a\n"), "af63dc4c8601ec8c");
+        assert_eq!(input_hash(DAY9), input_hash(DAY9.trim_end()));
+    }
+
+    #[test]
+    fn test_verify() {
+        let answers: Answers = ANSWERS.parse().unwrap();
+        let checks = verify(&answers, |day| match day {
+            9 => Ok(DAY9.to_owned()),
+            _ => Err(io::ErrorKind::NotFound.into()),
+        });
+        let day9 = checks.iter().filter(|c| c.day == 9).collect_vec();
+        assert_eq!(day9[0].status, Status::Pass);
+        assert_eq!(
+            day9[1].status,
+            Status::Fail {
+                expected: "3".into()
+            }
+        );
+        assert!(matches!(checks[0].status, Status::NoInput(_)));
+        let report = report(&checks);
+        assert!(report.starts_with("day  part  answer"));
+        assert!(report.contains("FAIL (expected 3)"));
+    }
+}